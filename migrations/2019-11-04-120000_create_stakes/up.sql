@@ -0,0 +1,11 @@
+CREATE TABLE stakes
+(
+    id             SERIAL PRIMARY KEY,
+    staker_address CHAR(44)  NOT NULL UNIQUE,
+    reward_address CHAR(44)  NOT NULL,
+    validator_key  CHAR(192) NOT NULL,
+    block_number   INTEGER   NOT NULL
+);
+
+CREATE INDEX index_stakes_by_reward_address ON stakes (reward_address);
+CREATE INDEX index_stakes_by_validator_key ON stakes (validator_key);
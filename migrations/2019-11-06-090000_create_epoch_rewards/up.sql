@@ -0,0 +1,16 @@
+CREATE TABLE epoch_rewards
+(
+    id              SERIAL PRIMARY KEY,
+    epoch           INTEGER  NOT NULL,
+    block_hash      CHAR(64) NOT NULL,
+    staker_address  CHAR(44) NOT NULL,
+    reward_address  CHAR(44) NOT NULL,
+    num_slots       INTEGER  NOT NULL,
+    num_slashed     INTEGER  NOT NULL,
+    reward          BIGINT   NOT NULL,
+    slash_deduction BIGINT   NOT NULL,
+    payout          BIGINT   NOT NULL
+);
+
+CREATE INDEX index_epoch_rewards_by_epoch ON epoch_rewards (epoch);
+CREATE INDEX index_epoch_rewards_by_reward_address ON epoch_rewards (reward_address);
@@ -0,0 +1,10 @@
+CREATE TABLE transaction_messages
+(
+    id           SERIAL PRIMARY KEY,
+    txid         CHAR(64) NOT NULL,
+    block_number INTEGER  NOT NULL,
+    message      TEXT     NOT NULL
+);
+
+CREATE INDEX index_transaction_messages_by_txid ON transaction_messages (txid);
+CREATE INDEX index_transaction_messages_fulltext ON transaction_messages USING GIN (to_tsvector('simple', message));
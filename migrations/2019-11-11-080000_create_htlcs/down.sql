@@ -0,0 +1,2 @@
+DROP TABLE htlc_events;
+DROP TABLE htlcs;
@@ -0,0 +1,27 @@
+CREATE TABLE htlcs
+(
+    id                    SERIAL PRIMARY KEY,
+    address               CHAR(44) NOT NULL UNIQUE,
+    creation_txid         CHAR(64) NOT NULL,
+    creation_block_number INTEGER  NOT NULL,
+    sender                CHAR(44) NOT NULL,
+    recipient             CHAR(44) NOT NULL,
+    hash_algorithm        TEXT     NOT NULL,
+    hash_root             TEXT     NOT NULL,
+    hash_count            INTEGER  NOT NULL,
+    timeout               INTEGER  NOT NULL,
+    total_amount          BIGINT   NOT NULL
+);
+
+CREATE TABLE htlc_events
+(
+    id           SERIAL PRIMARY KEY,
+    address      CHAR(44) NOT NULL,
+    txid         CHAR(64) NOT NULL,
+    block_number INTEGER  NOT NULL,
+    kind         TEXT     NOT NULL,
+    amount       BIGINT   NOT NULL,
+    pre_image    TEXT
+);
+
+CREATE INDEX index_htlc_events_by_address ON htlc_events (address);
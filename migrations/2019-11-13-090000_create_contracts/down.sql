@@ -0,0 +1,2 @@
+DROP TABLE contract_parties;
+DROP TABLE contracts;
@@ -0,0 +1,18 @@
+CREATE TABLE contracts
+(
+    id                    SERIAL PRIMARY KEY,
+    address               CHAR(44) NOT NULL UNIQUE,
+    contract_type         TEXT     NOT NULL,
+    creation_txid         CHAR(64) NOT NULL,
+    creation_block_number INTEGER  NOT NULL
+);
+
+CREATE TABLE contract_parties
+(
+    id               SERIAL PRIMARY KEY,
+    contract_address CHAR(44) NOT NULL,
+    party_address    CHAR(44) NOT NULL,
+    role             TEXT     NOT NULL
+);
+
+CREATE INDEX index_contract_parties_by_party_address ON contract_parties (party_address);
@@ -0,0 +1,10 @@
+CREATE TABLE blocks
+(
+    id               SERIAL PRIMARY KEY,
+    block_hash       CHAR(64) NOT NULL UNIQUE,
+    block_number     INTEGER  NOT NULL,
+    is_macro         BOOLEAN  NOT NULL,
+    num_transactions INTEGER  NOT NULL
+);
+
+CREATE INDEX index_blocks_by_block_number ON blocks (block_number);
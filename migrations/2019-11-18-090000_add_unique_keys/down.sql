@@ -0,0 +1,4 @@
+ALTER TABLE contract_parties DROP CONSTRAINT contract_parties_contract_address_party_address_role_key;
+ALTER TABLE htlc_events DROP CONSTRAINT htlc_events_txid_address_kind_key;
+ALTER TABLE transaction_messages DROP CONSTRAINT transaction_messages_txid_key;
+ALTER TABLE transactions DROP CONSTRAINT transactions_txid_block_hash_key;
@@ -0,0 +1,18 @@
+-- Remove rows that were indexed twice, then make sure blocks can be indexed again without
+-- duplicating them
+
+DELETE FROM transactions a USING transactions b
+WHERE a.id > b.id AND a.txid = b.txid AND a.block_hash = b.block_hash;
+ALTER TABLE transactions ADD CONSTRAINT transactions_txid_block_hash_key UNIQUE (txid, block_hash);
+
+DELETE FROM transaction_messages a USING transaction_messages b
+WHERE a.id > b.id AND a.txid = b.txid;
+ALTER TABLE transaction_messages ADD CONSTRAINT transaction_messages_txid_key UNIQUE (txid);
+
+DELETE FROM htlc_events a USING htlc_events b
+WHERE a.id > b.id AND a.txid = b.txid AND a.address = b.address AND a.kind = b.kind;
+ALTER TABLE htlc_events ADD CONSTRAINT htlc_events_txid_address_kind_key UNIQUE (txid, address, kind);
+
+DELETE FROM contract_parties a USING contract_parties b
+WHERE a.id > b.id AND a.contract_address = b.contract_address AND a.party_address = b.party_address AND a.role = b.role;
+ALTER TABLE contract_parties ADD CONSTRAINT contract_parties_contract_address_party_address_role_key UNIQUE (contract_address, party_address, role);
@@ -0,0 +1 @@
+-- The backfilled rows can't be told apart from indexed ones, so they're kept.
@@ -0,0 +1,7 @@
+-- Blocks indexed before the blocks table existed. Only blocks with transactions are known, which
+-- is enough to tell how far the indexer got.
+INSERT INTO blocks (block_hash, block_number, is_macro, num_transactions)
+SELECT block_hash, MIN(block_number), FALSE, COUNT(*)
+FROM transactions
+GROUP BY block_hash
+ON CONFLICT DO NOTHING;
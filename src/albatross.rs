@@ -1,36 +1,100 @@
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::collections::HashMap;
 use std::str::{FromStr, Utf8Error};
 use std::fmt::{Display, Error as DisplayError, Formatter};
 use std::cmp::Ordering;
+use std::convert::TryFrom;
 
+use parking_lot::RwLock;
 use rocket::request::FromParam;
 use rocket::http::RawStr;
 
 use nimiq_network_primitives::networks::NetworkInfo;
 use nimiq_primitives::networks::NetworkId;
-use nimiq_hash::Blake2bHash;
+use nimiq_primitives::coin::Coin;
+use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::{Address, PublicKey};
 use nimiq_blockchain_albatross::chain_info::ChainInfo;
+use nimiq_block_albatross::{Block, MacroBlock};
+use nimiq_bls::bls12_381::CompressedPublicKey;
+use nimiq_primitives::policy::{epoch_at, macro_block_of, block_reward_at};
 use nimiq_blockchain_albatross::blockchain::BlockchainEvent;
 use nimiq_utils::observer::ListenerHandle;
 use nimiq_transaction::Transaction;
+use nimiq_account::Account;
+use nimiq_account::staking_contract::StakingContract;
 use nimiq::client::{Client, Consensus};
 use nimiq_validator::validator::Validator;
+use nimiq_mempool::{ReturnCode, MempoolEvent};
+use nimiq_network::connection::connection_info::ConnectionState;
+use beserial::Deserialize;
 
 use crate::resource::genesis::GenesisInfo;
-use crate::resource::block::BlockInfo;
+use crate::resource::block::{BlockInfo, PbftJustificationInfo};
+use crate::resource::verification::{BlockVerificationInfo, Validators};
 use crate::resource::transaction::{TransactionInfo, Confirmation};
-use crate::resource::account::{AccountInfo, AccountTransactionInfo};
-use crate::resource::metadata::MetadataStore;
+use crate::resource::account::{AccountInfo, AccountSummaryInfo, AccountTransactionInfo, StakerInfo, ActiveStakeInfo};
+use crate::resource::metadata::{MetadataStore, StakeMeta, HtlcEventMeta};
+use crate::resource::rewards::EpochRewardInfo;
+use crate::resource::htlc::{HtlcInfo, HtlcState};
+use crate::resource::contract::{ContractInfo, ContractRole};
+use crate::resource::broadcast::TransactionCheckInfo;
+use crate::resource::network::{NetworkStatusInfo, PeerInfo};
+use crate::resource::health::HealthInfo;
+use crate::resource::metadata::{parse_address, parse_hash};
+use crate::utils::nimiq_to_chrono;
+use crate::error::Error;
 
 
 
+/// Characters of the base32 encoding used in user-friendly addresses
+const ADDRESS_ALPHABET: &str = "0123456789ABCDEFGHJKLMNPQRSTUVXY";
+
+
 #[derive(Clone, Debug, Fail)]
 pub enum ParseError {
     #[fail(display = "Unrecognized identifier format: {}", _0)]
     Unrecognized(String),
     #[fail(display = "Invalid encoding: {}", _0)]
-    InvalidEncoding(Utf8Error)
+    InvalidEncoding(Utf8Error),
+    #[fail(display = "Invalid checksum in address: {}", address)]
+    InvalidChecksum { address: String, suggestion: Option<String> },
+    #[fail(display = "Invalid address: {}: {}", _0, _1)]
+    InvalidAddress(String, &'static str),
+    #[fail(display = "Hash must be 64 hex chars: {}", input)]
+    InvalidHash { input: String, suggestion: Option<String> },
+    #[fail(display = "Block number too large: {} (maximum is 4294967295)", _0)]
+    BlockNumberTooLarge(String),
+    #[fail(display = "Block range must be <from>..<to> with from <= to: {}", _0)]
+    InvalidRange(String),
+    #[fail(display = "Validator key must be a compressed BLS public key as 192 hex chars: {}", _0)]
+    InvalidValidatorKey(String),
+}
+
+impl ParseError {
+    /// The identifier that failed to parse
+    pub fn input(&self) -> Option<&str> {
+        match self {
+            ParseError::Unrecognized(input)
+            | ParseError::InvalidChecksum { address: input, .. }
+            | ParseError::InvalidAddress(input, _)
+            | ParseError::InvalidHash { input, .. }
+            | ParseError::BlockNumberTooLarge(input)
+            | ParseError::InvalidRange(input)
+            | ParseError::InvalidValidatorKey(input) => Some(input),
+            ParseError::InvalidEncoding(_) => None,
+        }
+    }
+
+    /// A corrected identifier, if we can guess what was meant
+    pub fn suggestion(&self) -> Option<&str> {
+        match self {
+            ParseError::InvalidChecksum { suggestion, .. }
+            | ParseError::InvalidHash { suggestion, .. } => suggestion.as_ref().map(String::as_str),
+            _ => None,
+        }
+    }
 }
 
 impl From<Utf8Error> for ParseError {
@@ -39,6 +103,91 @@ impl From<Utf8Error> for ParseError {
     }
 }
 
+fn is_hex(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Parses a hash. Fails with `InvalidHash` if `s` looks like hex, e.g. with a wrong length or a
+/// `0x` prefix.
+fn parse_hex_hash(s: &str) -> Result<Blake2bHash, ParseError> {
+    if s.len() == 64 && is_hex(s) {
+        if let Ok(hash) = s.parse::<Blake2bHash>() {
+            return Ok(hash);
+        }
+    }
+
+    let unprefixed = s.trim_start_matches("0x").trim_start_matches("0X");
+    if is_hex(unprefixed) {
+        let suggestion = if unprefixed.len() == 64 && unprefixed.len() != s.len() {
+            Some(unprefixed.to_lowercase())
+        } else {
+            None
+        };
+        Err(ParseError::InvalidHash { input: s.to_string(), suggestion })
+    }
+    else {
+        Err(ParseError::Unrecognized(s.to_string()))
+    }
+}
+
+/// Remainder of the IBAN-style check number modulo 97. Letters count as 10 to 35.
+fn iban_mod97(s: &str) -> u32 {
+    s.chars()
+        .filter_map(|c| c.to_digit(36))
+        .fold(0, |rem, digit| {
+            if digit < 10 { (rem * 10 + digit) % 97 } else { (rem * 100 + digit) % 97 }
+        })
+}
+
+/// Whether `s` starts like a user-friendly address, i.e. with `NQ` and the check digits
+fn looks_like_address(s: &str) -> bool {
+    let normalized = normalize_address(s);
+    normalized.starts_with("NQ") && normalized.chars().skip(2).take(2).filter(|c| c.is_ascii_digit()).count() == 2
+}
+
+/// Removes whitespace and converts to upper case
+fn normalize_address(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Parses a user-friendly address, explaining what's wrong with it if it fails. Whitespace and
+/// case are ignored. If the checksum is wrong, the address with the correct check digits is
+/// suggested.
+fn parse_user_friendly_address(s: &str) -> Result<Address, ParseError> {
+    if let Ok(address) = Address::from_user_friendly_address(s) {
+        return Ok(address);
+    }
+
+    let normalized = normalize_address(s);
+    if !normalized.is_ascii() || !normalized.starts_with("NQ") {
+        return Err(ParseError::InvalidAddress(s.to_string(), "address must start with NQ and contain only letters and digits"));
+    }
+    if normalized.len() != 36 {
+        return Err(ParseError::InvalidAddress(s.to_string(), "address must be 36 characters, not counting spaces"));
+    }
+    let (check, body) = normalized[2..].split_at(2);
+    if !check.chars().all(|c| c.is_ascii_digit()) || !body.chars().all(|c| ADDRESS_ALPHABET.contains(c)) {
+        return Err(ParseError::InvalidAddress(s.to_string(), "address contains invalid characters"));
+    }
+
+    let expected_check = format!("{:02}", 98 - iban_mod97(&format!("{}NQ00", body)));
+    if check != expected_check {
+        let groups = format!("NQ{}{}", expected_check, body).chars()
+            .collect::<Vec<char>>()
+            .chunks(4)
+            .map(|group| group.iter().collect::<String>())
+            .collect::<Vec<String>>();
+        return Err(ParseError::InvalidChecksum { address: s.to_string(), suggestion: Some(groups.join(" ")) });
+    }
+
+    // The checksum is right, so it only failed because of case or whitespace
+    Address::from_user_friendly_address(&normalized)
+        .map_err(|_| ParseError::InvalidAddress(s.to_string(), "address can't be decoded"))
+}
+
 
 #[derive(Clone, Debug)]
 pub enum BlockIdentifier {
@@ -54,14 +203,13 @@ impl FromStr for BlockIdentifier {
         if s.eq_ignore_ascii_case("genesis") {
             Ok(BlockIdentifier::Genesis)
         }
-        else if let Ok(hash) = s.parse::<Blake2bHash>() {
-            Ok(BlockIdentifier::Hash(hash))
-        }
-        else if let Ok(num) = s.parse::<u32>() {
-            Ok(BlockIdentifier::Number(num))
+        else if s.len() != 64 && !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+            s.parse::<u32>()
+                .map(BlockIdentifier::Number)
+                .map_err(|_| ParseError::BlockNumberTooLarge(s.to_string()))
         }
         else {
-            Err(ParseError::Unrecognized(s.to_string()))
+            parse_hex_hash(s).map(BlockIdentifier::Hash)
         }
     }
 }
@@ -87,6 +235,48 @@ impl Display for BlockIdentifier {
 }
 
 
+/// A half-open range of block numbers `<from>..<to>`, as used by the range download
+#[derive(Clone, Debug)]
+pub struct BlockRange {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl BlockRange {
+    pub fn len(&self) -> u32 {
+        self.to.saturating_sub(self.from)
+    }
+}
+
+impl FromStr for BlockRange {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, "..");
+        let from = parts.next().and_then(|from| from.parse::<u32>().ok());
+        let to = parts.next().and_then(|to| to.parse::<u32>().ok());
+        match (from, to) {
+            (Some(from), Some(to)) if from <= to => Ok(BlockRange { from, to }),
+            _ => Err(ParseError::InvalidRange(s.to_string())),
+        }
+    }
+}
+
+impl<'a> FromParam<'a> for BlockRange {
+    type Error = ParseError;
+
+    fn from_param(param: &'a RawStr) -> Result<Self, Self::Error> {
+        let param = param.url_decode()?;
+        param.parse()
+    }
+}
+
+impl Display for BlockRange {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), DisplayError> {
+        write!(f, "{}..{}", self.from, self.to)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TransactionIdentifier(pub Blake2bHash);
 
@@ -94,9 +284,12 @@ impl FromStr for TransactionIdentifier {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.parse::<Blake2bHash>()
-            .map_err(|_| ParseError::Unrecognized(s.to_string()))
-            .map(|hash| TransactionIdentifier(hash))
+        match parse_hex_hash(s) {
+            Ok(hash) => Ok(TransactionIdentifier(hash)),
+            // anything else is clearly meant to be a transaction hash too
+            Err(ParseError::Unrecognized(_)) => Err(ParseError::InvalidHash { input: s.to_string(), suggestion: None }),
+            Err(e) => Err(e),
+        }
     }
 }
 
@@ -134,17 +327,14 @@ impl FromStr for AccountIdentifier {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok(address) = Address::from_user_friendly_address(s) {
-            Ok(AccountIdentifier::Address(address))
+        if s.trim_start().get(..2).map(|prefix| prefix.eq_ignore_ascii_case("NQ")).unwrap_or(false) {
+            parse_user_friendly_address(s).map(AccountIdentifier::Address)
         }
         else if let Ok(pubkey) = s.parse() {
             Ok(AccountIdentifier::PublicKey(pubkey))
         }
-        else if let Ok(hash) = s.parse() {
-            Ok(AccountIdentifier::Hash(hash))
-        }
         else {
-            Err(ParseError::Unrecognized(s.to_string()))
+            parse_hex_hash(s).map(AccountIdentifier::Hash)
         }
     }
 }
@@ -179,6 +369,36 @@ impl Display for AccountIdentifier {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct ValidatorIdentifier(pub CompressedPublicKey);
+
+impl FromStr for ValidatorIdentifier {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode(s).ok()
+            .and_then(|raw| Deserialize::deserialize_from_vec(&raw).ok())
+            .map(ValidatorIdentifier)
+            .ok_or_else(|| ParseError::InvalidValidatorKey(s.to_string()))
+    }
+}
+
+impl<'a> FromParam<'a> for ValidatorIdentifier {
+    type Error = ParseError;
+
+    fn from_param(param: &'a RawStr) -> Result<Self, Self::Error> {
+        let param = param.url_decode()?;
+        debug!("ValidatorIdentifier::from_param: {:?}", param);
+        param.parse()
+    }
+}
+
+impl Display for ValidatorIdentifier {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), DisplayError> {
+        self.0.to_hex().fmt(f)
+    }
+}
+
 pub enum AnyIdentifier {
     Block(BlockIdentifier),
     Transaction(TransactionIdentifier),
@@ -189,24 +409,69 @@ impl FromStr for AnyIdentifier {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok(ident) = s.parse::<BlockIdentifier>() {
-            Ok(AnyIdentifier::Block(ident))
+        // Anything that is clearly meant as an identifier fails with the reason, the rest is
+        // unrecognized and can be searched for otherwise.
+        if looks_like_address(s) {
+            return parse_user_friendly_address(s)
+                .map(|address| AnyIdentifier::Account(AccountIdentifier::Address(address)));
         }
-        else if let Ok(ident) = s.parse::<TransactionIdentifier>() {
-            Ok(AnyIdentifier::Transaction(ident))
+
+        match s.parse::<BlockIdentifier>() {
+            Ok(ident) => return Ok(AnyIdentifier::Block(ident)),
+            Err(e @ ParseError::BlockNumberTooLarge(_)) => return Err(e),
+            Err(_) => {},
         }
-        else if let Ok(ident) = s.parse::<AccountIdentifier>() {
-            Ok(AnyIdentifier::Account(ident))
+        if let Ok(ident) = s.parse::<TransactionIdentifier>() {
+            return Ok(AnyIdentifier::Transaction(ident));
         }
-        else {
-            Err(ParseError::Unrecognized(s.to_string()))
+        if let Ok(ident) = s.parse::<AccountIdentifier>() {
+            return Ok(AnyIdentifier::Account(ident));
+        }
+
+        // Short hex strings are more likely words than truncated hashes
+        let unprefixed = s.trim_start_matches("0x").trim_start_matches("0X");
+        if is_hex(unprefixed) && unprefixed.len() >= 32 {
+            return parse_hex_hash(s).map(|hash| AnyIdentifier::Block(BlockIdentifier::Hash(hash)));
         }
+        Err(ParseError::Unrecognized(s.to_string()))
     }
 }
 
 
 struct Listeners {
     blockchain: ListenerHandle,
+    mempool: ListenerHandle,
+}
+
+/// How far the indexer got and whether it could reach the database. This is updated when blocks
+/// are indexed, so rendering pages doesn't need to query the database.
+#[derive(Clone, Debug, Default)]
+struct IndexerState {
+    indexed_block_number: Option<u32>,
+    database_reachable: bool,
+}
+
+impl IndexerState {
+    fn load(meta_store: &MetadataStore) -> Self {
+        match meta_store.get_indexed_block_number() {
+            Ok(indexed_block_number) => Self { indexed_block_number, database_reachable: true },
+            Err(e) => {
+                warn!("Failed to get indexed block number: {}", e);
+                Self { indexed_block_number: None, database_reachable: false }
+            },
+        }
+    }
+
+    fn indexed(&mut self, block_number: u32) {
+        self.indexed_block_number = Some(block_number);
+        self.database_reachable = true;
+    }
+
+    fn failed(&mut self, error: &Error) {
+        if let Error::Pool(_) = error {
+            self.database_reachable = false;
+        }
+    }
 }
 
 // Helper class bundling access to Albatross client and meta data store
@@ -214,9 +479,15 @@ pub struct Albatross {
     client: Client,
     // TODO: Remove? We have that ref in client now
     consensus: Arc<Consensus>,
-    validator: Arc<Validator>,
+    /// Only set if we're running as validator
+    validator: Option<Arc<Validator>>,
+    /// Not connected to the network, only serving the stored chain
+    offline: bool,
 
     meta_store: Arc<MetadataStore>,
+    indexer_state: Arc<RwLock<IndexerState>>,
+    /// Number of transactions in the mempool, kept up to date by the mempool listener
+    mempool_size: Arc<AtomicUsize>,
 
     pub genesis_info: GenesisInfo,
     pub genesis_hash: Blake2bHash,
@@ -225,67 +496,269 @@ pub struct Albatross {
 }
 
 impl Albatross {
-    pub fn new(client: Client, mut meta_store: MetadataStore) -> Self {
+    pub fn new(client: Client, mut meta_store: MetadataStore, offline: bool) -> Result<Self, Error> {
         let consensus = client.consensus();
-        let validator = client.validator()
-            .expect("Client must run as validator");
+        let validator = client.validator();
         let network_info = NetworkInfo::from_network_id(consensus.blockchain.network_id);
-        let genesis_info = GenesisInfo::from(network_info);
+        let genesis_info = GenesisInfo::try_from(network_info)?;
 
         // hard-code some account aliases from the genesis config
         // TODO: We could also fetch all initial accounts from the genesis block and label them
         meta_store.set_account_alias(genesis_info.staking_contract.clone(), "Staking Contract");
+        meta_store.set_staking_contract(genesis_info.staking_contract.clone());
 
+        let indexer_state = Arc::new(RwLock::new(IndexerState::load(&meta_store)));
         let meta_store = Arc::new(meta_store);
-        let listeners = Self::init_listeners(&consensus, &meta_store);
+        let mempool_size = Arc::new(AtomicUsize::new(consensus.mempool.get_transactions(usize::max_value(), 0f64).len()));
+        let listeners = Self::init_listeners(&consensus, &meta_store, &indexer_state, &mempool_size);
 
-        Self {
+        Ok(Self {
             client,
             consensus,
             validator,
+            offline,
             meta_store,
+            indexer_state,
+            mempool_size,
             genesis_info,
             genesis_hash: network_info.genesis_hash().clone(),
             listeners,
-        }
+        })
     }
 
-    fn init_listeners(consensus: &Arc<Consensus>, meta_store: &Arc<MetadataStore>) -> Listeners {
+    fn init_listeners(consensus: &Arc<Consensus>, meta_store: &Arc<MetadataStore>, indexer_state: &Arc<RwLock<IndexerState>>, mempool_size: &Arc<AtomicUsize>) -> Listeners {
         let weak_consensus = Arc::downgrade(consensus);
         let weak_meta_store = Arc::downgrade(meta_store);
+        let weak_indexer_state = Arc::downgrade(indexer_state);
         let blockchain = consensus.blockchain.notifier.write().register(move |event: &BlockchainEvent| {
             let consensus = upgrade_weak!(weak_consensus);
             let meta_store = upgrade_weak!(weak_meta_store);
-            match event {
-                BlockchainEvent::Extended(hash) => {
-                    let block = consensus.blockchain.get_block(&hash, true, true)
-                        .unwrap_or_else(|| panic!("Extended with unknown block: {}", hash));
-                    meta_store.push_block(&hash, &block);
-                },
-                BlockchainEvent::Finalized(hash) => {
-                    let block = consensus.blockchain.get_block(&hash, true, true)
-                        .unwrap_or_else(|| panic!("Extended with unknown block: {}", hash));
-                    meta_store.push_block(&hash, &block);
-                    meta_store.finalize_epoch(&hash, &block);
-                },
+            let indexer_state = upgrade_weak!(weak_indexer_state);
+            let (hash, finalized) = match event {
+                BlockchainEvent::Extended(hash) => (hash, false),
+                BlockchainEvent::Finalized(hash) => (hash, true),
                 BlockchainEvent::Rebranched(_old_chain, _new_chain) => {
                     // TODO: Do we need to do anything here?
+                    return;
                 }
             };
+            match Self::index_block(&consensus, &meta_store, hash, finalized) {
+                Ok(block_number) => indexer_state.write().indexed(block_number),
+                Err(e) => {
+                    error!("Failed to index block {}: {}", hash, e);
+                    indexer_state.write().failed(&e);
+                },
+            }
+        });
+
+        let weak_mempool_size = Arc::downgrade(mempool_size);
+        let mempool = consensus.mempool.notifier.write().register(move |event: &MempoolEvent| {
+            let mempool_size = upgrade_weak!(weak_mempool_size);
+            match event {
+                MempoolEvent::TransactionAdded(_, _) | MempoolEvent::TransactionRestored(_) => {
+                    mempool_size.fetch_add(1, AtomicOrdering::Relaxed);
+                },
+                MempoolEvent::TransactionMined(_) | MempoolEvent::TransactionEvicted(_) => {
+                    // don't wrap around if we missed an addition
+                    let mut size = mempool_size.load(AtomicOrdering::Relaxed);
+                    while size > 0 {
+                        match mempool_size.compare_exchange_weak(size, size - 1, AtomicOrdering::Relaxed, AtomicOrdering::Relaxed) {
+                            Ok(_) => break,
+                            Err(current) => size = current,
+                        }
+                    }
+                },
+            }
         });
 
         Listeners {
             blockchain,
+            mempool,
         }
     }
 
+    /// Indexes a block we were notified about and returns its block number. Finalized blocks also
+    /// conclude an epoch.
+    fn index_block(consensus: &Consensus, meta_store: &MetadataStore, hash: &Blake2bHash, finalized: bool) -> Result<u32, Error> {
+        let block = consensus.blockchain.get_block(hash, true, true)
+            .ok_or_else(|| Error::MissingInChain(format!("Block {}", hash)))?;
+        meta_store.push_block(hash, &block)?;
+
+        if finalized {
+            Self::record_epoch_rewards(consensus, meta_store, hash, &block)?;
+
+            let staking_contract = meta_store.get_staking_contract()
+                .map(|address| consensus.blockchain.state().accounts.get(address, None));
+            if let Some(Account::Staking(staking_contract)) = staking_contract {
+                meta_store.sync_stakes(block.block_number(), &staking_contract)?;
+            }
+        }
+        Ok(block.block_number())
+    }
+
+    /// Records the rewards of the epoch that was concluded by the macro block `block`
+    fn record_epoch_rewards(consensus: &Consensus, meta_store: &MetadataStore, hash: &Blake2bHash, block: &Block) -> Result<(), Error> {
+        let rewards = Self::compute_epoch_rewards(consensus, hash, block)?;
+        meta_store.finalize_epoch(block, &rewards)
+    }
+
+    /// Indexes the blocks in the chain store that aren't indexed yet, e.g. when running offline
+    /// over an existing data directory. Blocks that are already indexed are skipped by the store.
+    /// Macro blocks in the chain store are final, so their epoch rewards are recorded too.
+    pub fn reindex(&self) -> Result<(), Error> {
+        let head_block_number = self.block_number();
+        let from = self.meta_store.get_indexed_block_number()?
+            .map(|block_number| block_number + 1)
+            .unwrap_or(0);
+        if from > head_block_number {
+            return Ok(());
+        }
+        info!("Indexing blocks {} to {}", from, head_block_number);
+
+        for block_number in from ..= head_block_number {
+            let chain_info = self.consensus.blockchain.chain_store
+                .get_chain_info_at(block_number, true, None)
+                .ok_or_else(|| Error::MissingInChain(format!("Block #{}", block_number)))?;
+            let hash = chain_info.head.hash::<Blake2bHash>();
+            self.meta_store.push_block(&hash, &chain_info.head)?;
+            // the genesis block doesn't conclude an epoch
+            if chain_info.head.is_macro() && block_number > 0 {
+                Self::record_epoch_rewards(&self.consensus, &self.meta_store, &hash, &chain_info.head)?;
+            }
+            if block_number % 1000 == 0 {
+                debug!("Indexed block {}", block_number);
+            }
+        }
+
+        if let Some(staking_contract) = self.get_staking_contract() {
+            self.meta_store.sync_stakes(head_block_number, &staking_contract)?;
+        }
+        self.indexer_state.write().indexed(head_block_number);
+        info!("Indexed {} blocks", head_block_number - from + 1);
+        Ok(())
+    }
+
+    /// Computes the rewards for the epoch that was concluded by `block`
+    fn compute_epoch_rewards(consensus: &Consensus, block_hash: &Blake2bHash, block: &Block) -> Result<Vec<EpochRewardInfo>, Error> {
+        let macro_block = match block {
+            Block::Macro(macro_block) => macro_block,
+            Block::Micro(_) => return Err(Error::NotMacroBlock(block_hash.to_string())),
+        };
+        let epoch = epoch_at(macro_block.header.block_number);
+        let extrinsics = macro_block.extrinsics.as_ref()
+            .ok_or_else(|| Error::MissingInChain(format!("Extrinsics of block {}", block_hash)))?;
+
+        // the slots of this epoch were assigned by the macro block concluding the previous epoch
+        let prev_macro_block = consensus.blockchain.chain_store
+            .get_chain_info_at(macro_block_of(epoch.saturating_sub(1)), true, None)
+            .map(|chain_info| chain_info.head);
+        let slot_owners: Vec<(Address, Address)> = match prev_macro_block {
+            Some(Block::Macro(MacroBlock { extrinsics: Some(prev_extrinsics), .. })) => {
+                prev_extrinsics.slot_addresses.into_iter()
+                    .map(|slot| (slot.staker_address, slot.reward_address))
+                    .collect()
+            },
+            _ => {
+                warn!("Can't compute rewards for epoch {}: Previous macro block is unknown", epoch);
+                return Ok(vec![]);
+            },
+        };
+
+        let reward_pot = Self::compute_reward_pot(consensus, epoch)?;
+
+        Ok(EpochRewardInfo::compute(epoch, block_hash, &slot_owners, &extrinsics.slashed_set, reward_pot))
+    }
+
+    /// Sums up the reward pot of an epoch from its micro blocks, which each add the block reward
+    /// and their transaction fees. This doesn't depend on the current state of the chain, so it
+    /// also works for epochs that were finalized a while ago.
+    fn compute_reward_pot(consensus: &Consensus, epoch: u32) -> Result<Coin, Error> {
+        let mut reward_pot = 0u64;
+        for block_number in macro_block_of(epoch.saturating_sub(1)) + 1 .. macro_block_of(epoch) {
+            let chain_info = consensus.blockchain.chain_store
+                .get_chain_info_at(block_number, true, None)
+                .ok_or_else(|| Error::MissingInChain(format!("Block #{}", block_number)))?;
+            if let Block::Micro(micro_block) = chain_info.head {
+                let extrinsics = micro_block.extrinsics
+                    .ok_or_else(|| Error::MissingInChain(format!("Extrinsics of block #{}", block_number)))?;
+                reward_pot += u64::from(block_reward_at(block_number));
+                reward_pot += extrinsics.transactions.iter()
+                    .map(|transaction| u64::from(transaction.fee))
+                    .sum::<u64>();
+            }
+        }
+        Ok(Coin::from_u64_unchecked(reward_pot))
+    }
+
     pub fn network_id(&self) -> NetworkId {
         self.consensus.blockchain.network_id
     }
 
-    pub fn get_block_info(&self, ident: &BlockIdentifier) -> Option<BlockInfo> {
-        self.get_chain_info(ident)
-            .map(BlockInfo::from)
+    pub fn get_block_info(&self, ident: &BlockIdentifier) -> Result<BlockInfo, Error> {
+        let chain_info = self.get_chain_info(ident)
+            .ok_or_else(|| Error::NotFound("Block", ident.to_string()))?;
+        let is_macro = chain_info.head.is_macro();
+        let epoch = epoch_at(chain_info.head.block_number());
+
+        let mut block_info = self.get_block_info_from_chain_info(chain_info)?;
+        if is_macro {
+            block_info = block_info.with_rewards(self.meta_store.get_epoch_rewards(epoch)?);
+        }
+        Ok(block_info)
+    }
+
+    /// Builds the block info with verification and justification. The block doesn't need to be in
+    /// the chain store, e.g. for decoded blocks, so rewards aren't looked up.
+    pub fn get_block_info_from_chain_info(&self, chain_info: ChainInfo) -> Result<BlockInfo, Error> {
+        let validators = self.get_epoch_validators(epoch_at(chain_info.head.block_number()));
+        let verification = self.verify_block(&chain_info.head, validators.as_ref());
+
+        let justification = match (&chain_info.head, &validators) {
+            (Block::Macro(macro_block), Some(validators)) => macro_block.justification.as_ref()
+                .map(|proof| PbftJustificationInfo::new(proof, validators)),
+            _ => None,
+        };
+
+        let mut block_info = BlockInfo::try_from(chain_info)?.with_verification(verification);
+        if let Some(justification) = justification {
+            block_info = block_info.with_justification(justification);
+        }
+        Ok(block_info)
+    }
+
+    /// Verifies the signatures and justification of a block. `validators` are the validators of
+    /// the block's epoch.
+    pub fn verify_block(&self, block: &Block, validators: Option<&Validators>) -> BlockVerificationInfo {
+        let prev_seed = self.consensus.blockchain.chain_store
+            .get_chain_info(block.parent_hash(), false, None)
+            .map(|chain_info| chain_info.head.seed().clone());
+        let producer_key = self.get_block_producer_key(block.block_number(), block.view_number());
+
+        match block {
+            Block::Micro(micro_block) => BlockVerificationInfo::verify_micro_block(micro_block, producer_key.as_ref(), prev_seed.as_ref(), validators),
+            Block::Macro(macro_block) => BlockVerificationInfo::verify_macro_block(macro_block, producer_key.as_ref(), prev_seed.as_ref(), validators),
+        }
+    }
+
+    /// Returns the validators of an epoch. Those are elected by the macro block that concluded
+    /// the previous epoch.
+    pub fn get_epoch_validators(&self, epoch: u32) -> Option<Validators> {
+        if epoch == 0 {
+            return None;
+        }
+        let chain_info = self.consensus.blockchain.chain_store
+            .get_chain_info_at(macro_block_of(epoch - 1), true, None)?;
+        match chain_info.head {
+            Block::Macro(macro_block) => Some(Validators::from(macro_block.header.validators)),
+            Block::Micro(_) => None,
+        }
+    }
+
+    /// Returns the BLS key of the slot owner that was supposed to produce the block at this
+    /// block and view number.
+    pub fn get_block_producer_key(&self, block_number: u32, view_number: u32) -> Option<CompressedPublicKey> {
+        let (_, slot) = self.consensus.blockchain.get_block_producer_at(block_number, view_number, None)?;
+        Some(slot.public_key().compressed().clone())
     }
 
     pub fn get_chain_info(&self, ident: &BlockIdentifier) -> Option<ChainInfo> {
@@ -299,17 +772,21 @@ impl Albatross {
         }
     }
 
-    pub fn get_latest_blocks(&self, num: usize) -> Result<Vec<BlockInfo>, ()>{
+    pub fn get_block(&self, ident: &BlockIdentifier) -> Option<Block> {
+        self.get_chain_info(ident).map(|chain_info| chain_info.head)
+    }
+
+    pub fn get_latest_blocks(&self, num: usize) -> Result<Vec<BlockInfo>, Error> {
         let chain_store = &self.consensus.blockchain.chain_store;
         let mut block_hash = chain_store
             .get_head(None)
-            .ok_or(())?;
+            .ok_or_else(|| Error::MissingInChain("Head".to_string()))?;
         let mut latest_blocks = Vec::new();
 
         for _ in 0..num {
             if let Some(chain_info) = chain_store.get_chain_info(&block_hash, true, None) {
                 block_hash = chain_info.head.parent_hash().clone();
-                latest_blocks.push(BlockInfo::from(chain_info));
+                latest_blocks.push(BlockInfo::try_from(chain_info)?);
             }
             else {
                 break;
@@ -319,32 +796,126 @@ impl Albatross {
         Ok(latest_blocks)
     }
 
-    pub fn get_account_info(&self, ident: &AccountIdentifier) -> AccountInfo {
+    pub fn get_account_info(&self, ident: &AccountIdentifier) -> Result<AccountInfo, Error> {
         let address = ident.clone().into();
         let account = self.consensus.blockchain.state().accounts.get(&address, None);
         let alias = self.meta_store.get_account_alias(&address);
 
         let transactions = self.meta_store
-            .get_account_transactions(&address)
+            .get_account_transactions(&address)?
             .into_iter()
-            .map(|tx_meta| {
-                let block_hash = tx_meta.block_hash.parse().expect("Failed to parse block hash");
+            .map(|tx_meta| -> Result<AccountTransactionInfo, Error> {
+                let block_hash = parse_hash(&tx_meta.block_hash)?;
                 let block_tx_ids = tx_meta.tx_idx as usize;
                 let (transaction, chain_info) = self.get_transaction_from_block(&block_hash, block_tx_ids)
-                    .unwrap_or_else(|| panic!("Failed to fetch transaction from block chain: hash={}, tx_idx={}", block_hash, block_tx_ids));
+                    .ok_or_else(|| Error::MissingInChain(format!("Transaction {} in block {}", block_tx_ids, block_hash)))?;
                 let confirmation = Confirmation::new(self.block_number(), chain_info.head.block_number());
                 let tx_info = TransactionInfo::new(transaction, Some(block_hash), Some(block_tx_ids), confirmation, false);
                 let is_sender = tx_info.sender_address == address;
                 let is_recipient = tx_info.recipient_address == address;
-                AccountTransactionInfo::new(tx_info, is_sender, is_recipient)
+                Ok(AccountTransactionInfo::new(tx_info, is_sender, is_recipient))
             })
             .rev() // reverse, since we want the newest to be first
-            .collect();
+            .collect::<Result<_, Error>>()?;
 
         let genesis_balance = self.genesis_info.accounts.get(&address)
             .map(|account| account.balance());
 
-        AccountInfo::new(address, account, alias, transactions, genesis_balance)
+        let block_number = self.block_number();
+        let staker = match self.get_staking_contract() {
+            Some(staking_contract) => {
+                let reward_stakers = self.meta_store.get_stakes_by_reward_address(&address)?
+                    .iter()
+                    .map(|stake_meta| parse_address(&stake_meta.staker_address))
+                    .collect::<Result<Vec<Address>, Error>>()?;
+                StakerInfo::new(&address, &staking_contract, &reward_stakers, block_number)
+            },
+            None => None,
+        };
+
+        let rewards = self.meta_store.get_account_rewards(&address)?;
+        let htlc = self.get_htlc_info(&address)?;
+        let contracts = self.get_account_contracts(&address, None)?;
+
+        Ok(AccountInfo::new(address, account, alias, transactions, genesis_balance, staker, block_number)
+            .with_rewards(rewards)
+            .with_htlc(htlc)
+            .with_contracts(contracts))
+    }
+
+    /// Returns the contracts this address is a party of
+    pub fn get_account_contracts(&self, address: &Address, role: Option<ContractRole>) -> Result<Vec<ContractInfo>, Error> {
+        self.meta_store.get_account_contracts(address, role)?
+            .into_iter()
+            .map(|(contract_meta, party_meta)| ContractInfo::new(contract_meta, &party_meta))
+            .collect()
+    }
+
+    /// Returns the indexed lifecycle of an HTLC
+    pub fn get_htlc_info(&self, address: &Address) -> Result<Option<HtlcInfo>, Error> {
+        let htlc_meta = match self.meta_store.get_htlc(address)? {
+            Some(htlc_meta) => htlc_meta,
+            None => return Ok(None),
+        };
+        let events = self.meta_store.get_htlc_events(address)?;
+        HtlcInfo::new(htlc_meta, events, self.block_number()).map(Some)
+    }
+
+    /// Returns a page of indexed HTLCs, optionally only those in a specific state
+    pub fn get_htlc_infos(&self, state: Option<HtlcState>, limit: u32, offset: u32) -> Result<Vec<HtlcInfo>, Error> {
+        let block_number = self.block_number();
+        let htlc_metas = self.meta_store.get_htlcs(state, block_number, i64::from(limit), i64::from(offset))?;
+
+        let addresses: Vec<String> = htlc_metas.iter()
+            .map(|htlc_meta| htlc_meta.address.clone())
+            .collect();
+        let mut events: HashMap<String, Vec<HtlcEventMeta>> = HashMap::new();
+        for event_meta in self.meta_store.get_htlc_events_for(&addresses)? {
+            events.entry(event_meta.address.clone()).or_default().push(event_meta);
+        }
+
+        htlc_metas.into_iter()
+            .map(|htlc_meta| {
+                let events = events.remove(&htlc_meta.address).unwrap_or_default();
+                HtlcInfo::new(htlc_meta, events, block_number)
+            })
+            .collect()
+    }
+
+    /// Returns the active stakes that pay rewards to this address
+    pub fn get_reward_stakes(&self, address: &Address) -> Result<Vec<ActiveStakeInfo>, Error> {
+        let stake_metas = self.meta_store.get_stakes_by_reward_address(address)?;
+        self.resolve_stakes(stake_metas)
+    }
+
+    /// Returns the active stakes for this validator
+    pub fn get_validator_stakes(&self, validator_key: &CompressedPublicKey) -> Result<Vec<ActiveStakeInfo>, Error> {
+        let stake_metas = self.meta_store.get_stakes_by_validator_key(validator_key)?;
+        self.resolve_stakes(stake_metas)
+    }
+
+    /// Looks up indexed stakes in the staking contract
+    fn resolve_stakes(&self, stake_metas: Vec<StakeMeta>) -> Result<Vec<ActiveStakeInfo>, Error> {
+        let staking_contract = match self.get_staking_contract() {
+            Some(staking_contract) => staking_contract,
+            None => return Ok(vec![]),
+        };
+
+        let mut stakes = Vec::new();
+        for stake_meta in stake_metas {
+            let staker_address = parse_address(&stake_meta.staker_address)?;
+            if let Some(active) = staking_contract.active_stake_by_address.get(&staker_address) {
+                stakes.push(ActiveStakeInfo::from(active.as_ref()));
+            }
+        }
+        Ok(stakes)
+    }
+
+    pub fn get_staking_contract(&self) -> Option<StakingContract> {
+        match self.consensus.blockchain.state().accounts.get(&self.genesis_info.staking_contract, None) {
+            Account::Staking(staking_contract) => Some(staking_contract),
+            _ => None,
+        }
     }
 
     pub fn get_transaction_from_block(&self, block_hash: &Blake2bHash, tx_idx: usize) -> Option<(Transaction, ChainInfo)> {
@@ -354,36 +925,157 @@ impl Albatross {
         Some((transaction, chain_info))
     }
 
-    pub fn get_transaction_info(&self, ident: &TransactionIdentifier) -> Option<TransactionInfo> {
-        let meta = self.meta_store.get_transaction(&ident.0)?;
-        let block_hash = meta.block_hash.parse::<Blake2bHash>()
-            .expect("Failed to parse Blake2b hash");
+    pub fn get_transaction_info(&self, ident: &TransactionIdentifier) -> Result<TransactionInfo, Error> {
+        let meta = match self.meta_store.get_transaction(&ident.0)? {
+            Some(meta) => meta,
+            None => {
+                // not in a block yet, but maybe in the mempool
+                let transaction = self.get_mempool_transaction(&ident.0)
+                    .ok_or_else(|| Error::NotFound("Transaction", ident.to_string()))?;
+                return Ok(TransactionInfo::new(transaction, None, None, Confirmation::default(), true));
+            },
+        };
+        let block_hash = parse_hash(&meta.block_hash)?;
         let tx_idx = meta.tx_idx as usize;
-        let (transaction, chain_info) = self.get_transaction_from_block(&block_hash, tx_idx)?;
+        let (transaction, chain_info) = self.get_transaction_from_block(&block_hash, tx_idx)
+            .ok_or_else(|| Error::MissingInChain(format!("Transaction {} in block {}", tx_idx, block_hash)))?;
         let confirmation = Confirmation::new(self.block_number(), chain_info.head.block_number());
         let info = TransactionInfo::new(transaction, Some(block_hash), Some(tx_idx), confirmation, false);
-        Some(info)
+        Ok(info)
+    }
+
+    /// Returns a transaction from the block it was included in, or from the mempool
+    pub fn get_transaction(&self, ident: &TransactionIdentifier) -> Result<Transaction, Error> {
+        match self.meta_store.get_transaction(&ident.0)? {
+            Some(meta) => {
+                let block_hash = parse_hash(&meta.block_hash)?;
+                self.get_transaction_from_block(&block_hash, meta.tx_idx as usize)
+                    .map(|(transaction, _)| transaction)
+                    .ok_or_else(|| Error::MissingInChain(format!("Transaction {} in block {}", meta.tx_idx, block_hash)))
+            },
+            None => self.get_mempool_transaction(&ident.0)
+                .ok_or_else(|| Error::NotFound("Transaction", ident.to_string())),
+        }
+    }
+
+    pub fn get_mempool_transaction(&self, txid: &Blake2bHash) -> Option<Transaction> {
+        self.consensus.mempool.get_transaction(txid)
+            .map(|transaction| transaction.as_ref().clone())
+    }
+
+    /// Runs the validity checks on a transaction against the current state
+    pub fn check_transaction(&self, transaction: &Transaction) -> TransactionCheckInfo {
+        let state = self.consensus.blockchain.state();
+        let sender = state.accounts.get(&transaction.sender, None);
+        let recipient = state.accounts.get(&transaction.recipient, None);
+        TransactionCheckInfo::new(transaction, self.network_id(), self.block_number(), &sender, &recipient)
+    }
+
+    /// Pushes a transaction into the mempool, from where it's relayed to our peers
+    pub fn push_transaction(&self, transaction: Transaction) -> ReturnCode {
+        self.consensus.mempool.push_transaction(transaction)
+    }
+
+    /// Searches transaction messages
+    pub fn search_messages(&self, query: &str, limit: usize) -> Result<Vec<TransactionInfo>, Error> {
+        self.meta_store.search_messages(query, limit as i64)?
+            .iter()
+            .map(|message_meta| {
+                let txid = parse_hash(&message_meta.txid)?;
+                self.get_transaction_info(&TransactionIdentifier(txid))
+            })
+            .collect()
     }
 
     pub fn get_head_hash(&self) -> Blake2bHash {
         self.consensus.blockchain.head_hash()
     }
 
-    pub fn get_head_info(&self) -> BlockInfo {
+    pub fn get_head_info(&self) -> Result<BlockInfo, Error> {
         let ident = BlockIdentifier::Hash(self.get_head_hash());
-        self.get_block_info(&ident).expect("Expected block chain to have a head")
+        self.get_block_info(&ident)
+    }
+
+    pub fn is_consensus_established(&self) -> bool {
+        self.consensus.established()
+    }
+
+    pub fn is_validator(&self) -> bool {
+        self.validator.is_some()
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn mempool_size(&self) -> usize {
+        self.mempool_size.load(AtomicOrdering::Relaxed)
+    }
+
+    pub fn meta_store(&self) -> &MetadataStore {
+        &self.meta_store
+    }
+
+    pub fn num_peers(&self) -> usize {
+        self.consensus.network.peer_count()
+    }
+
+    /// Returns the peers we have an established connection to
+    pub fn get_peer_infos(&self) -> Vec<PeerInfo> {
+        let chain_store = &self.consensus.blockchain.chain_store;
+        let connections = self.consensus.network.connections.state();
+
+        connections.connection_iter()
+            .filter(|info| info.state() == ConnectionState::Established)
+            .filter_map(|info| {
+                let peer_address = info.peer_address()?;
+                let peer = info.peer();
+                let head_hash = peer.map(|peer| peer.head_hash.clone());
+                let head_block_number = head_hash.as_ref()
+                    .and_then(|hash| chain_store.get_chain_info(hash, false, None))
+                    .map(|chain_info| chain_info.head.block_number());
+                Some(PeerInfo::new(
+                    peer_address.as_uri().to_string(),
+                    format!("{:?}", peer_address.services),
+                    format!("{:?}", peer_address.protocol()),
+                    peer.map(|peer| peer.version),
+                    info.established_since(),
+                    head_hash,
+                    head_block_number,
+                ))
+            })
+            .collect()
+    }
+
+    pub fn get_health_info(&self) -> Result<HealthInfo, Error> {
+        let head_hash = self.get_head_hash();
+        let head = self.get_block(&BlockIdentifier::Hash(head_hash.clone()))
+            .ok_or_else(|| Error::MissingInChain(format!("Head block {}", head_hash)))?;
+        let indexer_state = self.indexer_state.read().clone();
+        Ok(HealthInfo::new(self.offline, self.is_consensus_established(), head.block_number(), nimiq_to_chrono(head.timestamp()),
+                           indexer_state.indexed_block_number, indexer_state.database_reachable))
+    }
+
+    pub fn get_network_status_info(&self) -> Result<NetworkStatusInfo, Error> {
+        Ok(NetworkStatusInfo::new(self.network_id().to_string(), self.is_validator(), self.is_consensus_established(),
+                                  self.get_peer_infos(), self.get_head_info()?))
     }
 
     pub fn block_number(&self) -> u32 {
         self.consensus.blockchain.block_number()
     }
 
-    pub fn get_account_infos(&self) -> Vec<AccountInfo> {
-        let addresses = self.meta_store.get_known_account_addresses();
+    pub fn get_account_summaries(&self) -> Result<Vec<AccountSummaryInfo>, Error> {
+        let addresses = self.meta_store.get_known_account_addresses()?;
+        let state = self.consensus.blockchain.state();
 
-        let mut account_infos: Vec<AccountInfo> = addresses.into_iter()
-            .map(|address| self.get_account_info(&AccountIdentifier::Address(address)))
-            .collect();
+        let mut account_infos = addresses.into_iter()
+            .map(|address| {
+                let account = state.accounts.get(&address, None);
+                let alias = self.meta_store.get_account_alias(&address);
+                AccountSummaryInfo::new(address, &account, alias)
+            })
+            .collect::<Vec<AccountSummaryInfo>>();
 
         // sort aliased first by name and then unaliased by address
         account_infos.sort_by(|a, b| {
@@ -402,12 +1094,93 @@ impl Albatross {
         }
         */
 
-        account_infos
+        Ok(account_infos)
     }
 }
 
 impl Drop for Albatross {
     fn drop(&mut self) {
-        self.consensus.blockchain.notifier.write().deregister(self.listeners.blockchain)
+        self.consensus.blockchain.notifier.write().deregister(self.listeners.blockchain);
+        self.consensus.mempool.notifier.write().deregister(self.listeners.mempool);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NULL_ADDRESS: &str = "NQ07 0000 0000 0000 0000 0000 0000 0000 0000";
+    const NULL_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn iban_mod97_matches_iban_check() {
+        assert_eq!(iban_mod97("WEST12345698765432GB82"), 1);
+        assert_eq!(iban_mod97("NQ00"), 91);
+        assert_eq!(iban_mod97("00000000000000000000000000000000NQ00"), 91);
+    }
+
+    #[test]
+    fn parse_hex_hash_suggests_unprefixed_hash() {
+        assert_eq!(parse_hex_hash(NULL_HASH).unwrap(), Blake2bHash::default());
+
+        match parse_hex_hash(&format!("0x{}", NULL_HASH)) {
+            Err(ParseError::InvalidHash { suggestion: Some(suggestion), .. }) => assert_eq!(suggestion, NULL_HASH),
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        match parse_hex_hash("abcd") {
+            Err(ParseError::InvalidHash { suggestion: None, .. }) => {},
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        match parse_hex_hash("hello") {
+            Err(ParseError::Unrecognized(_)) => {},
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn parse_user_friendly_address_ignores_case_and_whitespace() {
+        assert_eq!(parse_user_friendly_address(NULL_ADDRESS).unwrap(), Address::default());
+        assert_eq!(parse_user_friendly_address(&NULL_ADDRESS.to_lowercase()).unwrap(), Address::default());
+        assert_eq!(parse_user_friendly_address(&NULL_ADDRESS.replace(' ', "\t")).unwrap(), Address::default());
+    }
+
+    #[test]
+    fn parse_user_friendly_address_explains_errors() {
+        match parse_user_friendly_address("NQ08 0000 0000 0000 0000 0000 0000 0000 0000") {
+            Err(ParseError::InvalidChecksum { suggestion: Some(suggestion), .. }) => assert_eq!(suggestion, NULL_ADDRESS),
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        match parse_user_friendly_address("nq08 0000 0000 0000 0000 0000 0000 0000 0000") {
+            Err(ref e @ ParseError::InvalidChecksum { .. }) => assert_eq!(e.suggestion(), Some(NULL_ADDRESS)),
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        match parse_user_friendly_address("NQ07 0000") {
+            Err(ParseError::InvalidAddress(_, _)) => {},
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        match parse_user_friendly_address("NQ07 0000 0000 0000 0000 0000 0000 0000 000O") {
+            Err(ParseError::InvalidAddress(_, _)) => {},
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn any_identifier_reports_identifier_errors() {
+        match "NQ08 0000 0000 0000 0000 0000 0000 0000 0000".parse::<AnyIdentifier>() {
+            Err(ParseError::InvalidChecksum { .. }) => {},
+            Err(e) => panic!("Unexpected error: {}", e),
+            Ok(_) => panic!("Unexpected identifier"),
+        }
+        match "99999999999".parse::<AnyIdentifier>() {
+            Err(ParseError::BlockNumberTooLarge(_)) => {},
+            Err(e) => panic!("Unexpected error: {}", e),
+            Ok(_) => panic!("Unexpected identifier"),
+        }
+        match "hello world".parse::<AnyIdentifier>() {
+            Err(ParseError::Unrecognized(_)) => {},
+            Err(e) => panic!("Unexpected error: {}", e),
+            Ok(_) => panic!("Unexpected identifier"),
+        }
     }
 }
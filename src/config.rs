@@ -0,0 +1,494 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Serialize, Deserialize};
+use structopt::StructOpt;
+
+use nimiq_primitives::networks::NetworkId;
+use nimiq_network_primitives::address::peer_uri::PeerUri;
+
+
+/// Config file that is used if none is given and it exists
+const DEFAULT_CONFIG_FILE: &str = "explorer.toml";
+
+
+#[derive(Debug, Fail)]
+pub enum ConfigError {
+    #[fail(display = "Failed to read config file {}: {}", _0, _1)]
+    Read(String, std::io::Error),
+    #[fail(display = "Failed to parse config file {}: {}", _0, _1)]
+    Parse(String, toml::de::Error),
+    #[fail(display = "Unknown network: {} (expected dev-albatross, test-albatross or unit-albatross)", _0)]
+    UnknownNetwork(String),
+    #[fail(display = "Invalid seed peer: {}", _0)]
+    InvalidSeedPeer(String),
+    #[fail(display = "Invalid listen address: {}", _0)]
+    InvalidListenAddress(String),
+    #[fail(display = "Invalid log level: {} (expected error, warn, info, debug or trace)", _0)]
+    InvalidLogLevel(String),
+    #[fail(display = "No database URL configured. Set it in the config file, with --database-url or DATABASE_URL")]
+    MissingDatabaseUrl,
+    #[fail(display = "Invalid feature: {} (expected decoder, broadcast or metrics, optionally with a no- prefix or =true/false)", _0)]
+    InvalidFeature(String),
+    #[fail(display = "Can't run as validator while offline")]
+    ValidatorOffline,
+    #[fail(display = "Invalid value for {}: {}", _0, _1)]
+    InvalidEnvVar(&'static str, String),
+    #[fail(display = "{} directory does not exist: {}", _0, _1)]
+    MissingDirectory(&'static str, String),
+}
+
+
+/// Command line arguments. Each overrides the environment and the config file.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "albatross-explorer", about = "Block explorer for Nimiq Albatross")]
+pub struct CommandLine {
+    /// Config file
+    #[structopt(short = "c", long = "config", parse(from_os_str))]
+    config: Option<PathBuf>,
+
+    /// Network: dev-albatross, test-albatross or unit-albatross
+    #[structopt(long = "network")]
+    network: Option<String>,
+
+    /// Also run a validator. This needs validator keys in the data directory.
+    #[structopt(long = "validator")]
+    validator: bool,
+
+    /// Don't connect to the network, only serve and index the chain in the data directory
+    #[structopt(long = "offline")]
+    offline: bool,
+
+    /// Seed peers as URIs, e.g. `ws://seed.example.com:8443`. Can be given multiple times.
+    #[structopt(long = "seed-peer", use_delimiter = true)]
+    seed_peers: Vec<String>,
+
+    /// Public hostname of our node, which peers connect to
+    #[structopt(long = "host")]
+    host: Option<String>,
+
+    /// Port of our node's WebSocket server
+    #[structopt(long = "node-port")]
+    node_port: Option<u16>,
+
+    /// Directory for the chain data
+    #[structopt(long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// Address the web server listens on
+    #[structopt(long = "listen-address")]
+    listen_address: Option<String>,
+
+    /// Port the web server listens on
+    #[structopt(short = "p", long = "port")]
+    port: Option<u16>,
+
+    /// Postgres URL
+    #[structopt(long = "database-url")]
+    database_url: Option<String>,
+
+    #[structopt(long = "template-dir", parse(from_os_str))]
+    template_dir: Option<PathBuf>,
+
+    #[structopt(long = "static-dir", parse(from_os_str))]
+    static_dir: Option<PathBuf>,
+
+    /// Log level: error, warn, info, debug or trace
+    #[structopt(long = "log-level")]
+    log_level: Option<String>,
+
+    /// Turns a feature on or off, e.g. `decoder`, `no-decoder` or `decoder=false`. Can be given
+    /// multiple times.
+    #[structopt(long = "feature", use_delimiter = true)]
+    features: Vec<String>,
+}
+
+
+/// Environment variables. Each overrides the config file.
+#[derive(Debug, Default)]
+struct Environment {
+    /// `EXPLORER_CONFIG`
+    config: Option<PathBuf>,
+    /// `EXPLORER_NETWORK`
+    network: Option<String>,
+    /// `EXPLORER_VALIDATOR`
+    validator: Option<bool>,
+    /// `EXPLORER_OFFLINE`
+    offline: Option<bool>,
+    /// `EXPLORER_SEED_PEERS`, comma-separated
+    seed_peers: Vec<String>,
+    /// `EXPLORER_HOST`
+    host: Option<String>,
+    /// `EXPLORER_NODE_PORT`
+    node_port: Option<u16>,
+    /// `EXPLORER_DATA_DIR`
+    data_dir: Option<PathBuf>,
+    /// `EXPLORER_LISTEN_ADDRESS`
+    listen_address: Option<String>,
+    /// `EXPLORER_PORT`
+    port: Option<u16>,
+    /// `DATABASE_URL`
+    database_url: Option<String>,
+    /// `EXPLORER_TEMPLATE_DIR`
+    template_dir: Option<PathBuf>,
+    /// `EXPLORER_STATIC_DIR`
+    static_dir: Option<PathBuf>,
+    /// `EXPLORER_LOG_LEVEL`
+    log_level: Option<String>,
+    /// `EXPLORER_FEATURES`, comma-separated
+    features: Vec<String>,
+}
+
+impl Environment {
+    /// Reads the settings from environment variables. Empty variables count as not set.
+    fn from_vars(vars: &HashMap<String, String>) -> Result<Self, ConfigError> {
+        let var = |name: &str| vars.get(name)
+            .filter(|value| !value.is_empty())
+            .cloned();
+        let list = |name: &str| var(name)
+            .map(|value| value.split(',').map(|item| item.trim().to_string()).collect())
+            .unwrap_or_default();
+        fn parse<T: FromStr>(name: &'static str, value: Option<String>) -> Result<Option<T>, ConfigError> {
+            value.map(|value| value.parse::<T>().map_err(|_| ConfigError::InvalidEnvVar(name, value)))
+                .transpose()
+        }
+
+        Ok(Self {
+            config: var("EXPLORER_CONFIG").map(PathBuf::from),
+            network: var("EXPLORER_NETWORK"),
+            validator: parse("EXPLORER_VALIDATOR", var("EXPLORER_VALIDATOR"))?,
+            offline: parse("EXPLORER_OFFLINE", var("EXPLORER_OFFLINE"))?,
+            seed_peers: list("EXPLORER_SEED_PEERS"),
+            host: var("EXPLORER_HOST"),
+            node_port: parse("EXPLORER_NODE_PORT", var("EXPLORER_NODE_PORT"))?,
+            data_dir: var("EXPLORER_DATA_DIR").map(PathBuf::from),
+            listen_address: var("EXPLORER_LISTEN_ADDRESS"),
+            port: parse("EXPLORER_PORT", var("EXPLORER_PORT"))?,
+            database_url: var("DATABASE_URL"),
+            template_dir: var("EXPLORER_TEMPLATE_DIR").map(PathBuf::from),
+            static_dir: var("EXPLORER_STATIC_DIR").map(PathBuf::from),
+            log_level: var("EXPLORER_LOG_LEVEL"),
+            features: list("EXPLORER_FEATURES"),
+        })
+    }
+}
+
+
+/// Config file. Everything is optional and falls back to the defaults.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    network: NetworkSection,
+    http: HttpSection,
+    database: DatabaseSection,
+    log: LogSection,
+    features: FeaturesConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct NetworkSection {
+    network: Option<String>,
+    validator: Option<bool>,
+    offline: Option<bool>,
+    seed_peers: Vec<String>,
+    host: Option<String>,
+    port: Option<u16>,
+    data_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct HttpSection {
+    listen_address: Option<String>,
+    port: Option<u16>,
+    template_dir: Option<PathBuf>,
+    static_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DatabaseSection {
+    url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LogSection {
+    level: Option<String>,
+}
+
+/// Optional parts of the explorer that can be turned off
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeaturesConfig {
+    /// Raw data decoder
+    pub decoder: bool,
+    /// Broadcasting and checking transactions
+    pub broadcast: bool,
+    /// Prometheus metrics
+    pub metrics: bool,
+}
+
+impl FeaturesConfig {
+    /// Sets a feature from a command line switch like `decoder`, `no-decoder` or `decoder=false`
+    fn set(&mut self, switch: &str) -> Result<(), ConfigError> {
+        let invalid = || ConfigError::InvalidFeature(switch.to_string());
+        let (name, enabled) = match switch.find('=') {
+            Some(i) => (&switch[.. i], switch[i + 1 ..].parse::<bool>().map_err(|_| invalid())?),
+            None if switch.starts_with("no-") => (&switch[3 ..], false),
+            None => (switch, true),
+        };
+        match name {
+            "decoder" => self.decoder = enabled,
+            "broadcast" => self.broadcast = enabled,
+            "metrics" => self.metrics = enabled,
+            _ => return Err(invalid()),
+        }
+        Ok(())
+    }
+}
+
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        Self {
+            decoder: true,
+            broadcast: true,
+            metrics: true,
+        }
+    }
+}
+
+
+/// Validated configuration
+#[derive(Debug)]
+pub struct Config {
+    pub network_id: NetworkId,
+    pub validator: bool,
+    pub offline: bool,
+    pub seed_peers: Vec<PeerUri>,
+    pub host: String,
+    pub node_port: u16,
+    pub data_dir: PathBuf,
+
+    pub listen_address: IpAddr,
+    pub port: u16,
+    pub template_dir: PathBuf,
+    pub static_dir: PathBuf,
+
+    pub database_url: String,
+
+    pub log_level: log::Level,
+
+    pub features: FeaturesConfig,
+}
+
+impl Config {
+    /// Reads the command line, environment and config file, with that precedence.
+    pub fn load() -> Result<Self, ConfigError> {
+        let command_line = CommandLine::from_args();
+        // skip variables that aren't valid unicode, none of ours are
+        let vars = env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .collect::<HashMap<String, String>>();
+        let environment = Environment::from_vars(&vars)?;
+
+        let config_file = match command_line.config.as_ref().or(environment.config.as_ref()) {
+            Some(path) => ConfigFile::read(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => ConfigFile::read(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => ConfigFile::default(),
+        };
+
+        Self::from_sources(command_line, environment, config_file)
+    }
+
+    fn from_sources(command_line: CommandLine, env: Environment, file: ConfigFile) -> Result<Self, ConfigError> {
+        let network = command_line.network.or(env.network).or(file.network.network)
+            .unwrap_or_else(|| "dev-albatross".to_string());
+        let network_id = parse_network_id(&network)?;
+
+        let validator = command_line.validator || env.validator.or(file.network.validator)
+            .unwrap_or(false);
+        let offline = command_line.offline || env.offline.or(file.network.offline)
+            .unwrap_or(false);
+        if validator && offline {
+            return Err(ConfigError::ValidatorOffline);
+        }
+
+        // Transactions can't be broadcast without peers
+        let mut features = file.features;
+        for switch in env.features.iter().chain(&command_line.features) {
+            features.set(switch)?;
+        }
+        features.broadcast &= !offline;
+
+        let seed_peers = if !command_line.seed_peers.is_empty() { command_line.seed_peers }
+            else if !env.seed_peers.is_empty() { env.seed_peers }
+            else { file.network.seed_peers };
+        let seed_peers = seed_peers.iter()
+            .map(|uri| PeerUri::from_str(uri).map_err(|_| ConfigError::InvalidSeedPeer(uri.clone())))
+            .collect::<Result<Vec<PeerUri>, ConfigError>>()?;
+
+        let listen_address = command_line.listen_address.or(env.listen_address).or(file.http.listen_address)
+            .unwrap_or_else(|| "127.0.0.1".to_string());
+        let listen_address = listen_address.parse::<IpAddr>()
+            .map_err(|_| ConfigError::InvalidListenAddress(listen_address.clone()))?;
+
+        let log_level = command_line.log_level.or(env.log_level).or(file.log.level)
+            .unwrap_or_else(|| "info".to_string());
+        let log_level = log_level.parse::<log::Level>()
+            .map_err(|_| ConfigError::InvalidLogLevel(log_level.clone()))?;
+
+        let database_url = command_line.database_url.or(env.database_url).or(file.database.url)
+            .ok_or(ConfigError::MissingDatabaseUrl)?;
+
+        let template_dir = command_line.template_dir.or(env.template_dir).or(file.http.template_dir)
+            .unwrap_or_else(|| PathBuf::from("templates"));
+        check_directory("Template", &template_dir)?;
+        let static_dir = command_line.static_dir.or(env.static_dir).or(file.http.static_dir)
+            .unwrap_or_else(|| PathBuf::from("static"));
+        check_directory("Static", &static_dir)?;
+
+        Ok(Self {
+            network_id,
+            validator,
+            offline,
+            seed_peers,
+            host: command_line.host.or(env.host).or(file.network.host)
+                .unwrap_or_else(|| "explorer.albatross.nimiq.dev".to_string()),
+            node_port: command_line.node_port.or(env.node_port).or(file.network.port)
+                .unwrap_or(nimiq::config::consts::WS_DEFAULT_PORT + 1),
+            data_dir: command_line.data_dir.or(env.data_dir).or(file.network.data_dir)
+                .unwrap_or_else(|| PathBuf::from("data")),
+            listen_address,
+            port: command_line.port.or(env.port).or(file.http.port)
+                .unwrap_or(8000),
+            template_dir,
+            static_dir,
+            database_url,
+            log_level,
+            features,
+        })
+    }
+}
+
+impl ConfigFile {
+    fn read(path: &Path) -> Result<Self, ConfigError> {
+        let path_str = path.display().to_string();
+        let contents = fs::read_to_string(path)
+            .map_err(|e| ConfigError::Read(path_str.clone(), e))?;
+        toml::from_str(&contents)
+            .map_err(|e| ConfigError::Parse(path_str, e))
+    }
+}
+
+fn parse_network_id(s: &str) -> Result<NetworkId, ConfigError> {
+    match s {
+        "dev-albatross" => Ok(NetworkId::DevAlbatross),
+        "test-albatross" => Ok(NetworkId::TestAlbatross),
+        "unit-albatross" => Ok(NetworkId::UnitAlbatross),
+        _ => Err(ConfigError::UnknownNetwork(s.to_string())),
+    }
+}
+
+fn check_directory(name: &'static str, path: &Path) -> Result<(), ConfigError> {
+    if path.is_dir() {
+        Ok(())
+    }
+    else {
+        Err(ConfigError::MissingDirectory(name, path.display().to_string()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/templates");
+    const STATIC_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/static");
+
+    fn command_line(args: &[&str]) -> CommandLine {
+        let required = ["--database-url", "postgres://localhost/explorer", "--template-dir", TEMPLATE_DIR, "--static-dir", STATIC_DIR];
+        CommandLine::from_iter(Some("albatross-explorer").iter().chain(required.iter()).chain(args.iter()))
+    }
+
+    fn environment(vars: &[(&str, &str)]) -> Environment {
+        let vars = vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Environment::from_vars(&vars).unwrap()
+    }
+
+    fn config_file(contents: &str) -> ConfigFile {
+        toml::from_str(contents).unwrap()
+    }
+
+    #[test]
+    fn defaults() {
+        let config = Config::from_sources(command_line(&[]), Environment::default(), ConfigFile::default()).unwrap();
+        assert_eq!(config.host, "explorer.albatross.nimiq.dev");
+        assert_eq!(config.node_port, nimiq::config::consts::WS_DEFAULT_PORT + 1);
+        assert!(!config.validator);
+        assert!(!config.offline);
+    }
+
+    #[test]
+    fn command_line_overrides_env_overrides_file() {
+        let file = "[http]\nport = 8002\n[features]\ndecoder = false\nmetrics = true\n";
+        let env = [("EXPLORER_PORT", "8001"), ("EXPLORER_FEATURES", "no-metrics")];
+
+        let config = Config::from_sources(command_line(&[]), environment(&env), config_file(file)).unwrap();
+        assert_eq!(config.port, 8001);
+        assert!(!config.features.metrics);
+        assert!(!config.features.decoder);
+
+        let config = Config::from_sources(command_line(&["--port", "8003", "--feature", "metrics"]), environment(&env), config_file(file)).unwrap();
+        assert_eq!(config.port, 8003);
+        assert!(config.features.metrics);
+        assert!(!config.features.decoder);
+
+        let config = Config::from_sources(command_line(&[]), Environment::default(), config_file(file)).unwrap();
+        assert_eq!(config.port, 8002);
+        assert!(config.features.metrics);
+    }
+
+    #[test]
+    fn invalid_env_var() {
+        let vars = [("EXPLORER_OFFLINE".to_string(), "maybe".to_string())].iter().cloned().collect();
+        match Environment::from_vars(&vars) {
+            Err(ConfigError::InvalidEnvVar(name, value)) => {
+                assert_eq!(name, "EXPLORER_OFFLINE");
+                assert_eq!(value, "maybe");
+            },
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn feature_switches() {
+        let mut features = FeaturesConfig::default();
+        features.set("no-decoder").unwrap();
+        assert!(!features.decoder);
+        features.set("decoder").unwrap();
+        assert!(features.decoder);
+        features.set("broadcast=false").unwrap();
+        assert!(!features.broadcast);
+        assert!(features.set("broadcast=maybe").is_err());
+        assert!(features.set("no-such-feature").is_err());
+    }
+
+    #[test]
+    fn offline_disables_broadcast() {
+        let config = Config::from_sources(command_line(&["--offline", "--feature", "broadcast"]), Environment::default(), ConfigFile::default()).unwrap();
+        assert!(config.offline);
+        assert!(!config.features.broadcast);
+
+        let config = Config::from_sources(command_line(&[]), environment(&[("EXPLORER_OFFLINE", "true")]), ConfigFile::default()).unwrap();
+        assert!(config.offline);
+    }
+}
@@ -0,0 +1,71 @@
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+
+use crate::albatross::ParseError;
+use crate::resource::error::ErrorInfo;
+
+
+/// Errors of lookups in the chain and the meta data store, propagated up to the handlers
+#[derive(Debug, Fail)]
+pub enum Error {
+    #[fail(display = "{} not found: {}", _0, _1)]
+    NotFound(&'static str, String),
+    #[fail(display = "{}", _0)]
+    BadRequest(String),
+    #[fail(display = "{}", _0)]
+    Parse(ParseError),
+    #[fail(display = "Database query failed: {}", _0)]
+    Database(diesel::result::Error),
+    #[fail(display = "Database is not available: {}", _0)]
+    Pool(r2d2::Error),
+    #[fail(display = "Invalid data in database: {}", _0)]
+    InvalidData(String),
+    #[fail(display = "Missing in chain store: {}", _0)]
+    MissingInChain(String),
+    #[fail(display = "Failed to serialize: {}", _0)]
+    Serializing(String),
+    #[fail(display = "Invalid genesis config: {}", _0)]
+    InvalidGenesis(String),
+    #[fail(display = "Not a macro block: {}", _0)]
+    NotMacroBlock(String),
+}
+
+impl Error {
+    pub fn status(&self) -> Status {
+        match self {
+            Error::NotFound(..) => Status::NotFound,
+            Error::BadRequest(_) | Error::Parse(_) => Status::BadRequest,
+            Error::Pool(_) => Status::ServiceUnavailable,
+            Error::Database(_) | Error::InvalidData(_) | Error::MissingInChain(_) | Error::Serializing(_)
+            | Error::InvalidGenesis(_) | Error::NotMacroBlock(_) => Status::InternalServerError,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<diesel::result::Error> for Error {
+    fn from(e: diesel::result::Error) -> Self {
+        Error::Database(e)
+    }
+}
+
+impl From<r2d2::Error> for Error {
+    fn from(e: r2d2::Error) -> Self {
+        Error::Pool(e)
+    }
+}
+
+impl<'r> Responder<'r> for Error {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        if self.status().code >= 500 {
+            error!("{} {}: {}", request.method(), request.uri(), self);
+        }
+        ErrorInfo::from(&self).respond_to(request)
+    }
+}
@@ -16,28 +16,22 @@ mod utils;
 mod resource;
 mod albatross;
 mod schema;
+mod config;
+mod error;
 
-use std::env;
+use std::process;
 
 use futures::Future;
 use rocket_contrib::templates::Template;
 use rocket_contrib::serve::StaticFiles;
 use dotenv::dotenv;
 
-/*use nimiq_network_primitives::protocol::Protocol;
-use nimiq_network_primitives::services::ServiceFlags;
-use nimiq_database::lmdb::{open, LmdbEnvironment};
-use nimiq_database::Environment;
-use nimiq_utils::key_store::KeyStore;
-use nimiq_network::network_config::Seed;*/
-
 use nimiq::prelude::*;
-use nimiq::config::consts::WS_DEFAULT_PORT;
 use nimiq::config::config::FileStorageConfig;
-use nimiq_primitives::networks::NetworkId;
-use nimiq_network_primitives::networks::NetworkInfo;
 use nimiq::extras::deadlock::initialize_deadlock_detection;
+use nimiq_network::network_config::Seed;
 
+use crate::config::Config;
 use crate::resource::ResourceRenderer;
 use crate::resource::blockchain::*;
 use crate::resource::search::*;
@@ -47,68 +41,136 @@ use crate::resource::dashboard::*;
 use crate::resource::transaction::*;
 use crate::resource::error::*;
 use crate::resource::account::*;
-use crate::albatross::{Albatross, BlockIdentifier};
+use crate::resource::htlc::*;
+use crate::resource::contract::*;
+use crate::resource::decoder::*;
+use crate::resource::broadcast::*;
+use crate::resource::network::*;
+use crate::resource::health::*;
+use crate::resource::metrics::*;
+use crate::albatross::Albatross;
 use crate::resource::metadata::MetadataStore;
-use std::path::Path;
-
-
-const NETWORK_ID: NetworkId = NetworkId::DevAlbatross;
 
 
 fn main() {
     dotenv().ok();
 
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("Invalid configuration: {}", e);
+        process::exit(1);
+    });
+
     // init logging
-    simple_logger::init_with_level(log::Level::Info).unwrap();
+    simple_logger::init_with_level(config.log_level).unwrap();
 
     // initialize deadlock detection
     initialize_deadlock_detection();
 
     tokio::run(futures::future::lazy(move || {
         // connect to database
-        let db_url = env::var("DATABASE_URL")
-            .expect("DATABASE_URL must be set");
-        let meta_store = MetadataStore::new(db_url);
+        let meta_store = MetadataStore::new(&config.database_url).unwrap_or_else(|e| {
+            error!("Failed to connect to database: {}", e);
+            process::exit(1);
+        });
 
         // init Nimiq
-        let client: Client = ClientConfig::builder()
-            .network(NETWORK_ID)
+        let mut client_config = ClientConfig::builder();
+        client_config.network(config.network_id)
             .full()
-            .ws("explorer.albatross.nimiq.dev", WS_DEFAULT_PORT + 1)
-            .storage(FileStorageConfig::from_directory("./data"))
-            .validator()
+            .ws(config.host.clone(), config.node_port)
+            .storage(FileStorageConfig::from_directory(&config.data_dir));
+        if config.validator {
+            client_config.validator();
+        }
+        for seed_peer in &config.seed_peers {
+            client_config.seed(Seed::new_peer(seed_peer.clone()));
+        }
+        let client: Client = client_config
             .instantiate_client()
             .expect("Failed to configure Nimiq");
 
         // build albatross object, which also manages meta-data
-        let albatross = Albatross::new(client.clone(), meta_store);
+        let albatross = Albatross::new(client.clone(), meta_store, config.offline).unwrap_or_else(|e| {
+            error!("Failed to initialize explorer: {}", e);
+            process::exit(1);
+        });
 
         // start Nimiq
         client.initialize().unwrap();
-        client.connect().unwrap();
+        if config.offline {
+            info!("Running offline, indexing the stored chain");
+            if let Err(e) = albatross.reindex() {
+                error!("Failed to index the stored chain: {}", e);
+                process::exit(1);
+            }
+        }
+        else {
+            client.connect().unwrap();
+        }
 
         // init Rocket
         info!("Initializing Rocket");
-        let renderer = ResourceRenderer::default();
-        rocket::ignite()
+        let rocket_config = rocket::Config::build(rocket::config::Environment::active().unwrap_or(rocket::config::Environment::Development))
+            .address(config.listen_address.to_string())
+            .port(config.port)
+            .extra("template_dir", config.template_dir.display().to_string())
+            .finalize()
+            .expect("Failed to configure Rocket");
+        let renderer = ResourceRenderer::default()
+            .with_features(config.features.clone());
+        let request_metrics = RequestMetrics::default();
+
+        let mut rocket = rocket::custom(rocket_config)
             .attach(Template::fairing())
+            .attach(request_metrics.clone())
             .manage(albatross)
             .manage(renderer)
-            .register(catchers![not_found])
+            .manage(request_metrics)
+            .register(catchers![bad_request, not_found, internal_error, service_unavailable])
             .mount("/", routes![
                 get_dashboard,
                 get_blockchain,
                 get_block,
                 download_block,
+                download_blocks,
                 get_genesis,
                 get_transaction,
                 download_transaction,
                 get_accounts,
                 get_account,
+                get_htlcs,
                 get_search,
+                get_network_info,
+                get_health,
+            ])
+            .mount("/api", routes![
+                api_get_block,
+                api_get_transaction,
+                api_get_account,
+                api_get_account_stakes,
+                api_get_account_contracts,
+                api_get_validator_stakes,
+                api_get_htlcs,
+                api_get_network_info,
             ])
-            .mount("/static", StaticFiles::from("static"))
-            .launch();
+            .mount("/static", StaticFiles::from(&config.static_dir));
+
+        if config.features.decoder {
+            rocket = rocket
+                .mount("/", routes![get_decoder, post_decoder])
+                .mount("/api", routes![api_decode]);
+        }
+        if config.features.broadcast {
+            rocket = rocket
+                .mount("/", routes![get_broadcast, post_broadcast])
+                .mount("/api", routes![api_broadcast, api_check]);
+        }
+        if config.features.metrics {
+            rocket = rocket
+                .mount("/", routes![get_metrics]);
+        }
+
+        rocket.launch();
 
         Ok(())
     }));
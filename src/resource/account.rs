@@ -1,21 +1,28 @@
-use std::sync::Arc;
-
 use serde::Serialize;
+use chrono::{DateTime, Duration, Utc};
 use rocket_contrib::templates::Template;
 use rocket::State;
+use rocket_contrib::json::Json;
 
 use nimiq_keys::Address;
 use nimiq_primitives::account::AccountType;
 use nimiq_primitives::coin::Coin;
-use nimiq_account::Account;
+use nimiq_account::{Account, VestingContract};
+use nimiq_account::staking_contract::{StakingContract, ActiveStake, InactiveStake};
+use nimiq_primitives::policy::{epoch_at, macro_block_of, UNSTAKING_DELAY};
 use nimiq_transaction::account::htlc_contract::{AnyHash, HashAlgorithm};
 use nimiq_bls::bls12_381::CompressedPublicKey as BlsPublicKey;
 
 use crate::resource::ResourceRenderer;
-use crate::albatross::{Albatross, AccountIdentifier};
+use crate::albatross::{Albatross, AccountIdentifier, ValidatorIdentifier, ParseError};
+use crate::error::Error;
 use crate::utils::{serialize_with_format, serialize_address, serialize_address_opt,
-                   serialize_with_beserial, short_validator_key, serialize_with_format_opt};
+                   serialize_with_beserial, short_validator_key, serialize_with_format_opt,
+                   serialize_datetime_opt};
 use crate::resource::transaction::TransactionInfo;
+use crate::resource::rewards::EpochRewardInfo;
+use crate::resource::htlc::HtlcInfo;
+use crate::resource::contract::ContractInfo;
 
 
 #[derive(Clone, Debug, Serialize)]
@@ -56,11 +63,86 @@ pub struct ActiveStakeInfo {
     short_validator_key: String,
 }
 
+impl From<&ActiveStake> for ActiveStakeInfo {
+    fn from(active: &ActiveStake) -> Self {
+        Self {
+            balance: active.balance,
+            staker_address: active.staker_address.clone(),
+            reward_address: active.reward_address.clone(),
+            validator_key: active.validator_key.clone(),
+            short_validator_key: short_validator_key(&active.validator_key),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct InactiveStakeInfo {
     #[serde(serialize_with = "serialize_with_format")]
     balance: Coin,
+    #[serde(serialize_with = "serialize_address")]
+    staker_address: Address,
     retire_time: u32,
+
+    /// Block from which on the stake can be unparked
+    unpark_block: u32,
+    is_unparkable: bool,
+}
+
+impl InactiveStakeInfo {
+    pub fn new(staker_address: Address, inactive: &InactiveStake, block_number: u32) -> Self {
+        // Retired stake is released with the macro block concluding the epoch in which it was
+        // retired, but can only be withdrawn after the unstaking delay.
+        let unpark_block = macro_block_of(epoch_at(inactive.retire_time)) + UNSTAKING_DELAY;
+        Self {
+            balance: inactive.balance,
+            staker_address,
+            retire_time: inactive.retire_time,
+            unpark_block,
+            is_unparkable: block_number >= unpark_block,
+        }
+    }
+}
+
+/// Stakes in the staking contract related to an address
+#[derive(Clone, Debug, Serialize)]
+pub struct StakerInfo {
+    /// Active stake owned by this address
+    active_stake: Option<ActiveStakeInfo>,
+
+    /// Active stakes of other stakers that pay their rewards to this address
+    reward_stakes: Vec<ActiveStakeInfo>,
+
+    /// Inactive stake owned by this address
+    inactive_stake: Option<InactiveStakeInfo>,
+}
+
+impl StakerInfo {
+    /// Collects the stakes related to `address`. `reward_stakers` are the stakers that pay their
+    /// rewards to `address`, as found in the stake index. Returns `None` if there are no stakes.
+    pub fn new(address: &Address, staking_contract: &StakingContract, reward_stakers: &[Address], block_number: u32) -> Option<Self> {
+        let active_stake = staking_contract.active_stake_by_address.get(address)
+            .map(|active| ActiveStakeInfo::from(active.as_ref()));
+
+        let reward_stakes: Vec<ActiveStakeInfo> = reward_stakers.iter()
+            .filter(|staker_address| *staker_address != address)
+            .filter_map(|staker_address| staking_contract.active_stake_by_address.get(staker_address))
+            .map(|active| ActiveStakeInfo::from(active.as_ref()))
+            .collect();
+
+        let inactive_stake = staking_contract.inactive_stake_by_address.get(address)
+            .map(|inactive| InactiveStakeInfo::new(address.clone(), inactive, block_number));
+
+        if active_stake.is_none() && reward_stakes.is_empty() && inactive_stake.is_none() {
+            None
+        }
+        else {
+            Some(Self {
+                active_stake,
+                reward_stakes,
+                inactive_stake,
+            })
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -84,6 +166,120 @@ pub struct VestingContractInfo {
     pub step_amount: Coin,
     #[serde(serialize_with = "serialize_with_format")]
     pub total_amount: Coin,
+    pub schedule: VestingScheduleInfo,
+}
+
+/// Maximum number of vesting steps shown in the schedule, around the next unlock
+const MAX_VESTING_STEPS: u64 = 100;
+
+/// Used to estimate when future blocks will be produced
+const ESTIMATED_BLOCK_TIME_MS: i64 = 1000;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VestingStepInfo {
+    block_number: u32,
+
+    /// Amount released in this step
+    #[serde(serialize_with = "serialize_with_format")]
+    amount: Coin,
+
+    /// Total amount released up to and including this step
+    #[serde(serialize_with = "serialize_with_format")]
+    unlocked: Coin,
+
+    is_unlocked: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VestingScheduleInfo {
+    /// Amount that can be withdrawn at the current block height (including what was already
+    /// withdrawn)
+    #[serde(serialize_with = "serialize_with_format")]
+    unlocked: Coin,
+
+    #[serde(serialize_with = "serialize_with_format")]
+    locked: Coin,
+
+    next_unlock_block: Option<u32>,
+
+    #[serde(serialize_with = "serialize_datetime_opt")]
+    next_unlock_time: Option<DateTime<Utc>>,
+
+    steps: Vec<VestingStepInfo>,
+    steps_truncated: bool,
+
+    #[serde(serialize_with = "serialize_with_format")]
+    withdrawn: Coin,
+    withdrawals: Vec<TransactionInfo>,
+}
+
+impl VestingScheduleInfo {
+    pub fn new(vesting: &VestingContract, block_number: u32, withdrawals: Vec<TransactionInfo>) -> Self {
+        let total_amount = u64::from(vesting.total_amount);
+        let step_amount = u64::from(vesting.step_amount);
+        let unlocked = total_amount - u64::from(vesting.min_cap(block_number));
+
+        let (steps, steps_truncated, next_unlock_block) = Self::steps(vesting.start, vesting.step_blocks, step_amount, total_amount, block_number);
+
+        let next_unlock_time = next_unlock_block
+            .map(|step_block| Utc::now() + Duration::milliseconds(i64::from(step_block - block_number) * ESTIMATED_BLOCK_TIME_MS));
+
+        let withdrawn = withdrawals.iter()
+            .map(|tx| u64::from(tx.value) + u64::from(tx.fee))
+            .sum();
+
+        Self {
+            unlocked: Coin::from_u64_unchecked(unlocked),
+            locked: Coin::from_u64_unchecked(total_amount - unlocked),
+            next_unlock_block,
+            next_unlock_time,
+            steps,
+            steps_truncated,
+            withdrawn: Coin::from_u64_unchecked(withdrawn),
+            withdrawals,
+        }
+    }
+
+    /// Returns up to `MAX_VESTING_STEPS` steps around the next unlock, whether steps were left
+    /// out, and the block of the next unlock. Steps past the last block number are never reached.
+    fn steps(start: u32, step_blocks: u32, step_amount: u64, total_amount: u64, block_number: u32) -> (Vec<VestingStepInfo>, bool, Option<u32>) {
+        if step_blocks == 0 || step_amount == 0 {
+            return (vec![], false, None);
+        }
+        let step_block = |i: u64| -> Option<u32> {
+            let step_block = u64::from(start) + i * u64::from(step_blocks);
+            if step_block > u64::from(u32::max_value()) { None } else { Some(step_block as u32) }
+        };
+
+        let num_steps = (total_amount + step_amount - 1) / step_amount;
+        let num_unlocked = if block_number < start { 0 } else { u64::from((block_number - start) / step_blocks) };
+        let next_step = (num_unlocked + 1).min(num_steps + 1);
+        let next_unlock_block = if next_step <= num_steps { step_block(next_step) } else { None };
+
+        // center the window on the next step, but keep it full at the start and end
+        let last = (next_step.saturating_sub(MAX_VESTING_STEPS / 2).max(1) + MAX_VESTING_STEPS - 1).min(num_steps);
+        let first = last.saturating_sub(MAX_VESTING_STEPS - 1).max(1);
+
+        let mut steps = Vec::new();
+        let mut steps_truncated = first > 1 || last < num_steps;
+        for i in first ..= last {
+            let step_block = match step_block(i) {
+                Some(step_block) => step_block,
+                None => {
+                    steps_truncated = true;
+                    break;
+                },
+            };
+            let step_unlocked = (i * step_amount).min(total_amount);
+            steps.push(VestingStepInfo {
+                block_number: step_block,
+                amount: Coin::from_u64_unchecked(step_unlocked - ((i - 1) * step_amount).min(total_amount)),
+                unlocked: Coin::from_u64_unchecked(step_unlocked),
+                is_unlocked: step_block <= block_number,
+            });
+        }
+        (steps, steps_truncated, next_unlock_block)
+    }
 }
 
 
@@ -118,12 +314,23 @@ pub struct AccountInfo {
 
     #[serde(serialize_with = "serialize_with_format_opt")]
     genesis_balance: Option<Coin>,
+
+    /// Stakes in the staking contract owned by or paying rewards to this address
+    staker: Option<StakerInfo>,
+
+    /// Epoch rewards paid to this address
+    rewards: Vec<EpochRewardInfo>,
+
+    /// Indexed lifecycle, if this is an HTLC
+    htlc: Option<HtlcInfo>,
+
+    /// Contracts this address is a party of
+    contracts: Vec<ContractInfo>,
 }
 
 
 impl AccountInfo {
-    // TODO: also pass in the active stakes if the address is a reward or staker address
-    pub fn new(address: Address, account: Account, primary_alias: Option<String>, transactions: Vec<AccountTransactionInfo>, genesis_balance: Option<Coin>) -> Self {
+    pub fn new(address: Address, account: Account, primary_alias: Option<String>, transactions: Vec<AccountTransactionInfo>, genesis_balance: Option<Coin>, staker: Option<StakerInfo>, block_number: u32) -> Self {
         let account_type = account.account_type();
         let is_empty = account.is_initial();
 
@@ -144,27 +351,13 @@ impl AccountInfo {
             }),
 
             Account::Staking(staking) => {
-                // drop this mapping in order to be able to unwrap the Arc's
-                drop(staking.active_stake_by_address);
-
-                let active_stake: Vec<ActiveStakeInfo> = staking.active_stake_sorted.into_iter().map(|active| {
-                    let active = Arc::try_unwrap(active).unwrap();
-                    let short_validator_key = short_validator_key(&active.validator_key);
-                    ActiveStakeInfo {
-                        balance: active.balance,
-                        staker_address: active.staker_address,
-                        reward_address: active.reward_address,
-                        validator_key: active.validator_key,
-                        short_validator_key,
-                    }
-                }).collect();
-
-                let inactive_stake: Vec<InactiveStakeInfo> = staking.inactive_stake_by_address.into_iter().map(|(_, inactive)| {
-                    InactiveStakeInfo {
-                        balance: inactive.balance,
-                        retire_time: inactive.retire_time,
-                    }
-                }).collect();
+                let active_stake: Vec<ActiveStakeInfo> = staking.active_stake_sorted.iter()
+                    .map(|active| ActiveStakeInfo::from(active.as_ref()))
+                    .collect();
+
+                let inactive_stake: Vec<InactiveStakeInfo> = staking.inactive_stake_by_address.iter()
+                    .map(|(staker_address, inactive)| InactiveStakeInfo::new(staker_address.clone(), inactive, block_number))
+                    .collect();
 
                 debug!("active stakes: {}", active_stake.len());
                 for stake in &active_stake {
@@ -179,6 +372,12 @@ impl AccountInfo {
             },
 
             Account::Vesting(vesting) => {
+                let withdrawals = transactions.iter()
+                    .filter(|tx| tx.is_sender)
+                    .map(|tx| tx.tx.clone())
+                    .collect();
+                let schedule = VestingScheduleInfo::new(&vesting, block_number, withdrawals);
+
                 AccountData::Vesting(VestingContractInfo {
                     balance: vesting.balance,
                     owner: vesting.owner,
@@ -186,6 +385,7 @@ impl AccountInfo {
                     step_blocks: vesting.step_blocks,
                     step_amount: vesting.step_amount,
                     total_amount: vesting.total_amount,
+                    schedule,
                 })
             }
         };
@@ -205,8 +405,27 @@ impl AccountInfo {
             // TODO This will later be filled form aliases stored in the database
             aliases: vec![],
             genesis_balance,
+            staker,
+            rewards: vec![],
+            htlc: None,
+            contracts: vec![],
         }
     }
+
+    pub fn with_rewards(mut self, rewards: Vec<EpochRewardInfo>) -> Self {
+        self.rewards = rewards;
+        self
+    }
+
+    pub fn with_htlc(mut self, htlc: Option<HtlcInfo>) -> Self {
+        self.htlc = htlc;
+        self
+    }
+
+    pub fn with_contracts(mut self, contracts: Vec<ContractInfo>) -> Self {
+        self.contracts = contracts;
+        self
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -229,13 +448,99 @@ impl AccountTransactionInfo {
 
 
 #[get("/account/<ident>")]
-pub fn get_account(ident: AccountIdentifier, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Option<Template> {
-    let account_info = albatross.get_account_info(&ident);
-    Some(renderer.render("account", account_info, &albatross))
+pub fn get_account(ident: Result<AccountIdentifier, ParseError>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, Error> {
+    let ident = ident?;
+    let account_info = albatross.get_account_info(&ident)?;
+    Ok(renderer.render("account", account_info, &albatross))
+}
+
+#[get("/account/<ident>")]
+pub fn api_get_account(ident: Result<AccountIdentifier, ParseError>, albatross: State<Albatross>) -> Result<Json<AccountInfo>, Error> {
+    let ident = ident?;
+    albatross.get_account_info(&ident).map(Json)
+}
+
+#[get("/account/<ident>/stakes")]
+pub fn api_get_account_stakes(ident: Result<AccountIdentifier, ParseError>, albatross: State<Albatross>) -> Result<Json<Vec<ActiveStakeInfo>>, Error> {
+    let ident = ident?;
+    albatross.get_reward_stakes(&ident.into()).map(Json)
+}
+
+#[get("/validator/<ident>/stakes")]
+pub fn api_get_validator_stakes(ident: Result<ValidatorIdentifier, ParseError>, albatross: State<Albatross>) -> Result<Json<Vec<ActiveStakeInfo>>, Error> {
+    let ident = ident?;
+    albatross.get_validator_stakes(&ident.0).map(Json)
+}
+
+/// Summary of an account for the account list. Unlike `AccountInfo` this only needs the accounts
+/// tree and doesn't look up transactions, stakes or contracts in the database.
+#[derive(Clone, Debug, Serialize)]
+pub struct AccountSummaryInfo {
+    #[serde(serialize_with = "serialize_address")]
+    pub address: Address,
+
+    #[serde(serialize_with = "serialize_with_format")]
+    pub account_type: AccountType,
+    is_basic: bool,
+    is_empty: bool,
+
+    #[serde(serialize_with = "serialize_with_format")]
+    balance: Coin,
+
+    pub primary_alias: Option<String>,
 }
 
+impl AccountSummaryInfo {
+    pub fn new(address: Address, account: &Account, primary_alias: Option<String>) -> Self {
+        let account_type = account.account_type();
+        Self {
+            address,
+            account_type,
+            is_basic: account_type == AccountType::Basic,
+            is_empty: account.is_initial(),
+            balance: account.balance(),
+            primary_alias,
+        }
+    }
+}
+
+
 #[get["/accounts"]]
-pub fn get_accounts(albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Template {
-    let account_infos = albatross.get_account_infos();
-    renderer.render("accounts", account_infos, &albatross)
+pub fn get_accounts(albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, Error> {
+    let account_summaries = albatross.get_account_summaries()?;
+    Ok(renderer.render("accounts", account_summaries, &albatross))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vesting_steps_around_next_unlock() {
+        // 1000 steps of 10 blocks starting at block 100, and we're in step 150
+        let (steps, steps_truncated, next_unlock_block) = VestingScheduleInfo::steps(100, 10, 5, 5000, 1595);
+        assert_eq!(next_unlock_block, Some(1600));
+        assert!(steps_truncated);
+        assert_eq!(steps.len() as u64, MAX_VESTING_STEPS);
+        assert_eq!(steps.first().unwrap().block_number, 100 + 100 * 10);
+        assert!(steps.iter().any(|step| step.block_number == 1600 && !step.is_unlocked));
+        assert!(steps.iter().any(|step| step.block_number == 1590 && step.is_unlocked));
+    }
+
+    #[test]
+    fn vesting_steps_fully_unlocked() {
+        let (steps, steps_truncated, next_unlock_block) = VestingScheduleInfo::steps(100, 10, 5, 5000, 100_000);
+        assert_eq!(next_unlock_block, None);
+        assert!(steps_truncated);
+        assert_eq!(steps.last().unwrap().block_number, 100 + 1000 * 10);
+        assert!(steps.iter().all(|step| step.is_unlocked));
+    }
+
+    #[test]
+    fn vesting_steps_past_last_block_number() {
+        let (steps, _, next_unlock_block) = VestingScheduleInfo::steps(u32::max_value() - 15, 10, 5, 5000, 0);
+        assert_eq!(next_unlock_block, Some(u32::max_value() - 5));
+        assert_eq!(steps.len(), 1);
+    }
 }
@@ -1,24 +1,35 @@
+use std::convert::TryFrom;
+use std::io::{self, Cursor, Read};
+
 use serde::Serialize;
 use chrono::{DateTime, Utc};
 use rocket_contrib::templates::Template;
 use rocket::State;
+use rocket::request::Form;
+use rocket_contrib::json::Json;
 
 use nimiq_hash::{Hash, Blake2bHash};
-use nimiq_block_albatross::{Block, BlockHeader, MacroHeader, MicroHeader, ForkProof, ViewChangeProof};
+use nimiq_block_albatross::{Block, BlockHeader, MacroHeader, MicroHeader, ForkProof, ViewChangeProof, PbftProof};
 use nimiq_blockchain_albatross::chain_info::ChainInfo;
-use nimiq_bls::bls12_381::CompressedSignature;
-use nimiq_primitives::policy::epoch_at;
+use nimiq_bls::bls12_381::{CompressedSignature, CompressedPublicKey};
+use nimiq_primitives::policy::{epoch_at, SLOTS, TWO_THIRD_SLOTS};
 use nimiq_collections::grouped_list::GroupedList;
 use nimiq_primitives::coin::Coin;
 use nimiq_keys::Address;
 
 use crate::utils::{serialize_with_format, serialize_with_format_opt, serialize_with_beserial,
                    short_hash, null_hash_opt, nimiq_to_chrono, serialize_datetime,
-                   serialize_with_hex, serialize_bitset};
+                   serialize_with_hex, serialize_bitset, short_validator_key, printable_utf8,
+                   hex_dump, HexDumpLine};
 use crate::resource::transaction::TransactionInfo;
-use crate::resource::{ResourceRenderer, Download};
+use crate::resource::verification::{BlockVerificationInfo, Validators};
+use crate::resource::rewards::EpochRewardInfo;
+use crate::resource::{ResourceRenderer, Download, DownloadFormat, StreamDownload};
 use crate::{Albatross, BlockIdentifier};
+use crate::albatross::{BlockRange, ParseError};
+use crate::error::Error;
 use nimiq_collections::bitset::BitSet;
+use beserial::Serialize as BeSerialize;
 
 
 #[derive(Clone, Debug, Serialize)]
@@ -165,6 +176,96 @@ pub struct MacroBlockInfo {
     slash_fine: Coin,
     #[serde(serialize_with = "serialize_bitset")]
     slashed_set: BitSet,
+    justification: Option<PbftJustificationInfo>,
+    rewards: Vec<EpochRewardInfo>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidatorSlotsInfo {
+    /// Index of the validator in the validator list of the epoch
+    validator_idx: u16,
+
+    #[serde(serialize_with = "serialize_with_beserial")]
+    validator_key: CompressedPublicKey,
+    short_validator_key: String,
+
+    /// First slot of this validator
+    first_slot: u16,
+    num_slots: u16,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PbftPhaseInfo {
+    /// Slots of the validators that signed this phase
+    #[serde(serialize_with = "serialize_bitset")]
+    signed_slots: BitSet,
+
+    /// Voting weight, i.e. the number of slots that signed
+    votes: u16,
+
+    /// Whether the votes reached the two-thirds threshold
+    threshold_reached: bool,
+
+    /// Validators that didn't sign this phase
+    missing_validators: Vec<ValidatorSlotsInfo>,
+}
+
+impl PbftPhaseInfo {
+    fn new(signers: &BitSet, validators: &Validators) -> Self {
+        let mut signed_slots = BitSet::new();
+        let mut votes = 0;
+        let mut missing_validators = Vec::new();
+        let mut first_slot = 0;
+
+        for (validator_idx, group) in validators.iter_groups().enumerate() {
+            let num_slots = group.0;
+            if signers.contains(validator_idx) {
+                for slot in first_slot .. first_slot + num_slots {
+                    signed_slots.insert(slot as usize);
+                }
+                votes += num_slots;
+            }
+            else {
+                let validator_key = group.1.compressed().clone();
+                missing_validators.push(ValidatorSlotsInfo {
+                    validator_idx: validator_idx as u16,
+                    short_validator_key: short_validator_key(&validator_key),
+                    validator_key,
+                    first_slot,
+                    num_slots,
+                });
+            }
+            first_slot += num_slots;
+        }
+
+        Self {
+            signed_slots,
+            votes,
+            threshold_reached: votes >= TWO_THIRD_SLOTS,
+            missing_validators,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PbftJustificationInfo {
+    prepare: PbftPhaseInfo,
+    commit: PbftPhaseInfo,
+
+    /// Number of slots needed for a phase to succeed
+    threshold: u16,
+    num_slots: u16,
+}
+
+impl PbftJustificationInfo {
+    pub fn new(proof: &PbftProof, validators: &Validators) -> Self {
+        Self {
+            prepare: PbftPhaseInfo::new(&proof.prepare.signers, validators),
+            commit: PbftPhaseInfo::new(&proof.commit.signers, validators),
+            threshold: TWO_THIRD_SLOTS,
+            num_slots: SLOTS,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -173,6 +274,8 @@ pub struct MicroBlockInfo {
     transactions: Vec<TransactionInfo>,
     #[serde(serialize_with = "serialize_with_hex")]
     extra_data: Vec<u8>,
+    extra_data_message: Option<String>,
+    extra_data_dump: Vec<HexDumpLine>,
     fork_proofs: Vec<ForkProofInfo>,
 
     #[serde(serialize_with = "serialize_with_beserial")]
@@ -206,17 +309,55 @@ pub struct BlockInfo {
     macro_info: Option<MacroBlockInfo>,
     #[serde(flatten)]
     micro_info: Option<MicroBlockInfo>,
+
+    verification: Option<BlockVerificationInfo>,
+}
+
+impl BlockInfo {
+    pub fn with_verification(mut self, verification: BlockVerificationInfo) -> Self {
+        self.verification = Some(verification);
+        self
+    }
+
+    pub fn with_justification(mut self, justification: PbftJustificationInfo) -> Self {
+        if let Some(macro_info) = &mut self.macro_info {
+            macro_info.justification = Some(justification);
+        }
+        self
+    }
+
+    pub fn with_rewards(mut self, rewards: Vec<EpochRewardInfo>) -> Self {
+        if let Some(macro_info) = &mut self.macro_info {
+            macro_info.rewards = rewards;
+        }
+        self
+    }
+}
+
+/// Block info without a body, e.g. for decoded block headers
+impl From<BlockHeader> for BlockInfo {
+    fn from(header: BlockHeader) -> Self {
+        BlockInfo {
+            header: BlockHeaderInfo::from(header),
+            macro_info: None,
+            micro_info: None,
+            verification: None,
+        }
+    }
 }
 
-impl From<ChainInfo> for BlockInfo {
-    fn from(chain_info: ChainInfo) -> Self {
+/// Fails if the block has no body
+impl TryFrom<ChainInfo> for BlockInfo {
+    type Error = Error;
+
+    fn try_from(chain_info: ChainInfo) -> Result<Self, Error> {
         let next_block_hash = chain_info.main_chain_successor;
-        let block_hash = chain_info.head.hash();
+        let block_hash: Blake2bHash = chain_info.head.hash();
 
-        match chain_info.head {
+        Ok(match chain_info.head {
             Block::Macro(block) => {
                 let extrinsics = block.extrinsics
-                    .unwrap_or_else(|| panic!("Micro block is missing extrinsics: {}", block_hash));
+                    .ok_or_else(|| Error::MissingInChain(format!("Extrinsics of macro block {}", block_hash)))?;
 
                 let slots = GroupedList::from(extrinsics.slot_addresses).iter_groups().cloned()
                     .map(|g| SlotInfo {
@@ -231,13 +372,16 @@ impl From<ChainInfo> for BlockInfo {
                         slots,
                         slash_fine: extrinsics.slash_fine,
                         slashed_set: extrinsics.slashed_set,
+                        justification: None,
+                        rewards: vec![],
                     }),
                     micro_info: None,
+                    verification: None,
                 }
             },
             Block::Micro(block) => {
                 let extrinsics = block.extrinsics
-                    .unwrap_or_else(|| panic!("Micro block is missing extrinsics: {}", block_hash));
+                    .ok_or_else(|| Error::MissingInChain(format!("Extrinsics of micro block {}", block_hash)))?;
 
                 let transactions = extrinsics.transactions.into_iter()
                     .map(TransactionInfo::from)
@@ -257,25 +401,131 @@ impl From<ChainInfo> for BlockInfo {
                     micro_info: Some(MicroBlockInfo {
                         num_transactions: transactions.len(),
                         transactions,
+                        extra_data_message: printable_utf8(&extrinsics.extra_data),
+                        extra_data_dump: hex_dump(&extrinsics.extra_data),
                         extra_data: extrinsics.extra_data,
                         fork_proofs,
                         signature: block.justification.signature,
                         view_change_proof,
                     }),
+                    verification: None,
                 }
             },
-        }
+        })
     }
 }
 
 #[get("/block/<ident>")]
-pub fn get_block(ident: BlockIdentifier, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Option<Template> {
+pub fn get_block(ident: Result<BlockIdentifier, ParseError>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, Error> {
+    let ident = ident?;
     let block_info = albatross.get_block_info(&ident)?;
-    Some(renderer.render("block", block_info, &albatross))
+    Ok(renderer.render("block", block_info, &albatross))
+}
+
+#[get("/block/<ident>")]
+pub fn api_get_block(ident: Result<BlockIdentifier, ParseError>, albatross: State<Albatross>) -> Result<Json<BlockInfo>, Error> {
+    let ident = ident?;
+    albatross.get_block_info(&ident).map(Json)
+}
+
+/// Maximum number of blocks in a range download
+const MAX_DOWNLOAD_RANGE: u32 = 10_000;
+
+#[derive(FromForm)]
+pub struct BlockDownloadParams {
+    format: Option<DownloadFormat>,
+    /// Only download the block header
+    header: Option<bool>,
 }
 
-#[get("/block/<ident>/download")]
-pub fn download_block(ident: BlockIdentifier, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Download, ()> {
-    let chain_info = albatross.get_chain_info(&ident).ok_or(())?;
-    renderer.download(chain_info.head).map_err(|e| warn!("Download failed: {}", e))
-}
\ No newline at end of file
+#[get("/block/<ident>/download?<params..>")]
+pub fn download_block(ident: Result<BlockIdentifier, ParseError>, params: Form<BlockDownloadParams>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Download, Error> {
+    let ident = ident?;
+    let format = params.format.unwrap_or_default();
+    let block = albatross.get_block(&ident)
+        .ok_or_else(|| Error::NotFound("Block", ident.to_string()))?;
+    let block_number = block.block_number();
+    let block_hash: Blake2bHash = block.hash();
+
+    if params.header.unwrap_or(false) {
+        let header = match block {
+            Block::Macro(macro_block) => BlockHeader::Macro(macro_block.header),
+            Block::Micro(micro_block) => BlockHeader::Micro(micro_block.header),
+        };
+        let name = format!("header-{}-{}", block_number, block_hash);
+        renderer.download(&header, || Ok(BlockHeaderInfo::from(header.clone())), &name, format)
+    }
+    else {
+        let name = format!("block-{}-{}", block_number, block_hash);
+        renderer.download(&block, || albatross.get_block_info(&ident), &name, format)
+    }
+}
+
+/// Streams the blocks in a range. Each block is prefixed with its serialized size as a big-endian
+/// `u32`.
+#[get("/blocks/<range>/download")]
+pub fn download_blocks(range: Result<BlockRange, ParseError>, albatross: State<Albatross>) -> Result<StreamDownload<BlockStream>, Error> {
+    let range = range?;
+    if range.len() > MAX_DOWNLOAD_RANGE {
+        return Err(Error::BadRequest(format!("Can't download more than {} blocks at once", MAX_DOWNLOAD_RANGE)));
+    }
+    if range.from > albatross.block_number() {
+        return Err(Error::BadRequest(format!("Range starts after the head at block {}", albatross.block_number())));
+    }
+    let filename = format!("blocks-{}-{}.bin", range.from, range.to);
+    Ok(StreamDownload::new(filename, BlockStream::new(albatross.inner(), range)))
+}
+
+/// Reads blocks from the chain store one at a time, as they're being streamed
+pub struct BlockStream<'a> {
+    albatross: &'a Albatross,
+    next: u32,
+    end: u32,
+    buf: Cursor<Vec<u8>>,
+}
+
+impl<'a> BlockStream<'a> {
+    pub fn new(albatross: &'a Albatross, range: BlockRange) -> Self {
+        Self {
+            albatross,
+            next: range.from,
+            end: range.to,
+            buf: Cursor::new(Vec::new()),
+        }
+    }
+
+    /// Serializes the next block into the buffer. Returns `false` at the end of the range, or if
+    /// the block doesn't exist (yet).
+    fn fill_buf(&mut self) -> io::Result<bool> {
+        if self.next >= self.end {
+            return Ok(false);
+        }
+        let block = match self.albatross.get_block(&BlockIdentifier::Number(self.next)) {
+            Some(block) => block,
+            None => {
+                self.end = self.next;
+                return Ok(false);
+            },
+        };
+        self.next += 1;
+
+        let size = BeSerialize::serialized_size(&block);
+        let mut buf = Vec::with_capacity(size + 4);
+        BeSerialize::serialize(&(size as u32), &mut buf)
+            .and_then(|_| BeSerialize::serialize(&block, &mut buf))
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        self.buf = Cursor::new(buf);
+        Ok(true)
+    }
+}
+
+impl<'a> Read for BlockStream<'a> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.buf.read(out)?;
+            if n > 0 || out.is_empty() || !self.fill_buf()? {
+                return Ok(n);
+            }
+        }
+    }
+}
@@ -5,6 +5,7 @@ use rocket::State;
 use rocket::request::{FromForm, Form};
 
 use crate::albatross::Albatross;
+use crate::error::Error;
 use crate::resource::ResourceRenderer;
 use crate::resource::block::BlockInfo;
 
@@ -22,7 +23,7 @@ pub struct BlockchainParams {
 }
 
 #[get("/blockchain?<params..>")]
-pub fn get_blockchain(params: Form<BlockchainParams>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, ()> {
+pub fn get_blockchain(params: Form<BlockchainParams>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, Error> {
     let num_blocks = params.num_blocks.unwrap_or(10);
     let latest_blocks = albatross.get_latest_blocks(num_blocks)?;
     Ok(renderer.render("blockchain", BlockchainInfo {
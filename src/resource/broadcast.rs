@@ -0,0 +1,297 @@
+use std::io::Read;
+
+use serde::Serialize;
+use rocket::State;
+use rocket::Data;
+use rocket::http::ContentType;
+use rocket::request::Form;
+use rocket::response::Redirect;
+use rocket::response::status::BadRequest;
+use rocket_contrib::templates::Template;
+use rocket_contrib::json::Json;
+
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_transaction::{Transaction, TransactionFlags};
+use nimiq_account::Account;
+use nimiq_primitives::account::AccountType;
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_primitives::policy::TRANSACTION_VALIDITY_WINDOW;
+use nimiq_mempool::ReturnCode;
+
+use crate::albatross::Albatross;
+use crate::resource::ResourceRenderer;
+use crate::resource::verification::VerificationResult;
+use crate::resource::decoder::{decode_hex, deserialize_exact, DecodeError};
+use crate::resource::transaction_proof::TransactionProof;
+use crate::utils::{serialize_with_format, serialize_with_format_opt, short_hash};
+
+
+/// Maximum size of a posted transaction
+const MAX_TRANSACTION_SIZE: u64 = 64 * 1024;
+
+
+/// Result of the checks we run on a transaction before it's pushed into the mempool
+#[derive(Clone, Debug, Serialize)]
+pub struct TransactionCheckInfo {
+    #[serde(serialize_with = "serialize_with_format")]
+    txid: Blake2bHash,
+    short_txid: String,
+
+    /// `true` if all checks passed
+    is_valid: bool,
+
+    /// Transaction is for the network we're on
+    network_id: VerificationResult,
+
+    /// Signature and proof format of the sender
+    signature: VerificationResult,
+
+    /// Validity window includes the next block
+    validity_window: VerificationResult,
+
+    /// Sender can pay value and fee
+    balance: VerificationResult,
+
+    /// Proof decodes and its signatures verify
+    proof: VerificationResult,
+
+    /// Recipient account has the type the transaction says it has
+    contract_type: VerificationResult,
+
+    /// Balances before and after the transaction would be applied
+    #[serde(serialize_with = "serialize_with_format")]
+    sender_balance: Coin,
+    #[serde(serialize_with = "serialize_with_format_opt")]
+    sender_balance_after: Option<Coin>,
+    #[serde(serialize_with = "serialize_with_format")]
+    recipient_balance: Coin,
+    #[serde(serialize_with = "serialize_with_format_opt")]
+    recipient_balance_after: Option<Coin>,
+}
+
+impl TransactionCheckInfo {
+    /// Checks a transaction. `block_number` is the current head, and `sender` and `recipient`
+    /// are the accounts in the current state.
+    pub fn new(transaction: &Transaction, network_id: NetworkId, block_number: u32, sender: &Account, recipient: &Account) -> Self {
+        let txid: Blake2bHash = transaction.hash();
+        let sender_balance = sender.balance();
+        let recipient_balance = recipient.balance();
+
+        let network_id_check = VerificationResult::check(transaction.network_id == network_id,
+            format!("Transaction is for {}, but we're on {}", transaction.network_id, network_id));
+
+        let signature = VerificationResult::from_result(transaction.verify(transaction.network_id));
+
+        // The transaction would be included in the next block at the earliest
+        let next_block_number = block_number + 1;
+        let validity_window = VerificationResult::check(transaction.is_valid_at(next_block_number),
+            format!("Transaction is valid from block {} to {}, but the next block is {}",
+                    transaction.validity_start_height,
+                    transaction.validity_start_height.saturating_add(TRANSACTION_VALIDITY_WINDOW),
+                    next_block_number));
+
+        let total = transaction.value.checked_add(transaction.fee);
+        let balance = match total {
+            Some(total) => VerificationResult::check(sender_balance >= total,
+                format!("Sender has a balance of {}, but value and fee are {}", sender_balance, total)),
+            None => VerificationResult::failed("Value and fee overflow"),
+        };
+
+        let proof = match TransactionProof::decode(transaction) {
+            Ok(proof) => proof.verify(transaction),
+            Err(e) => VerificationResult::failed(format!("Invalid proof: {}", e)),
+        };
+
+        let contract_type = check_contract_type(transaction, recipient);
+
+        // A transaction to itself only pays the fee
+        let is_self_transaction = transaction.sender == transaction.recipient;
+        let sender_balance_after = total
+            .and_then(|total| sender_balance.checked_sub(total))
+            .map(|balance| if is_self_transaction { balance + transaction.value } else { balance });
+        let recipient_balance_after = if is_self_transaction {
+            sender_balance_after
+        } else {
+            recipient_balance.checked_add(transaction.value)
+        };
+
+        let is_valid = [&network_id_check, &signature, &validity_window, &balance, &proof, &contract_type].iter()
+            .all(|check| check.is_verified());
+
+        Self {
+            short_txid: short_hash(&txid),
+            txid,
+            is_valid,
+            network_id: network_id_check,
+            signature,
+            validity_window,
+            balance,
+            proof,
+            contract_type,
+            sender_balance,
+            sender_balance_after,
+            recipient_balance,
+            recipient_balance_after,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+}
+
+/// Contracts must be created at an unused address. Otherwise the recipient type must match the
+/// account, where unused addresses are basic accounts.
+fn check_contract_type(transaction: &Transaction, recipient: &Account) -> VerificationResult {
+    if transaction.flags.contains(TransactionFlags::CONTRACT_CREATION) {
+        return VerificationResult::check(recipient.is_initial(),
+            format!("There already is an account at {}", transaction.recipient.to_user_friendly_address()));
+    }
+
+    let account_type = if recipient.is_initial() { AccountType::Basic } else { recipient.account_type() };
+    VerificationResult::check(account_type == transaction.recipient_type,
+        format!("Recipient is a {:?} account, but the transaction is for a {:?} account", account_type, transaction.recipient_type))
+}
+
+#[derive(Debug, Fail)]
+pub enum BroadcastError {
+    #[fail(display = "{}", _0)]
+    Decode(DecodeError),
+    #[fail(display = "Transaction failed validity checks")]
+    Invalid,
+    #[fail(display = "Fee is too low for the mempool")]
+    FeeTooLow,
+    #[fail(display = "Transaction was rejected by the mempool as invalid")]
+    Rejected,
+    #[fail(display = "Transaction was filtered by the mempool")]
+    Filtered,
+}
+
+impl From<DecodeError> for BroadcastError {
+    fn from(e: DecodeError) -> Self {
+        BroadcastError::Decode(e)
+    }
+}
+
+impl BroadcastError {
+    /// Error of a mempool return code. Transactions that were accepted now or before are fine.
+    pub fn from_return_code(return_code: ReturnCode) -> Option<Self> {
+        match return_code {
+            ReturnCode::Accepted | ReturnCode::Known => None,
+            ReturnCode::FeeTooLow => Some(BroadcastError::FeeTooLow),
+            ReturnCode::Invalid => Some(BroadcastError::Rejected),
+            ReturnCode::Filtered => Some(BroadcastError::Filtered),
+        }
+    }
+}
+
+/// Broadcast form, with the input and outcome of the last attempt
+#[derive(Clone, Debug, Serialize)]
+pub struct BroadcastInfo {
+    data: String,
+    error: Option<String>,
+    check: Option<TransactionCheckInfo>,
+}
+
+impl BroadcastInfo {
+    fn new(data: String, error: Option<String>, check: Option<TransactionCheckInfo>) -> Self {
+        Self {
+            data,
+            error,
+            check,
+        }
+    }
+}
+
+/// Checks the transaction and pushes it into the mempool, if it's valid and `dry_run` isn't set.
+/// The check is returned in both cases, if the transaction could be decoded.
+fn broadcast(data: &[u8], dry_run: bool, albatross: &Albatross) -> (Option<TransactionCheckInfo>, Result<Blake2bHash, BroadcastError>) {
+    let transaction: Transaction = match deserialize_exact(data) {
+        Ok(transaction) => transaction,
+        Err(e) => return (None, Err(e.into())),
+    };
+
+    let check = albatross.check_transaction(&transaction);
+    if !check.is_valid() {
+        return (Some(check), Err(BroadcastError::Invalid));
+    }
+    if dry_run {
+        let txid = check.txid.clone();
+        return (Some(check), Ok(txid));
+    }
+
+    let txid = check.txid.clone();
+    let result = match BroadcastError::from_return_code(albatross.push_transaction(transaction)) {
+        Some(e) => Err(e),
+        None => Ok(txid),
+    };
+    (Some(check), result)
+}
+
+
+#[derive(FromForm)]
+pub struct BroadcastForm {
+    data: String,
+    /// Only check the transaction, without broadcasting it
+    check: Option<bool>,
+}
+
+#[get("/broadcast")]
+pub fn get_broadcast(albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Template {
+    renderer.render("broadcast", BroadcastInfo::new(String::new(), None, None), &albatross)
+}
+
+/// Broadcasts a hex-encoded transaction and redirects to its page, where it's shown as pending.
+#[post("/broadcast", data = "<form>")]
+pub fn post_broadcast(form: Form<BroadcastForm>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Redirect, Template> {
+    let dry_run = form.check.unwrap_or(false);
+    let (check, result) = match decode_hex(&form.data) {
+        Ok(data) => broadcast(&data, dry_run, &albatross),
+        Err(e) => (None, Err(e.into())),
+    };
+
+    match result {
+        Ok(_) if dry_run => Err(renderer.render("broadcast", BroadcastInfo::new(form.data.clone(), None, check), &albatross)),
+        Ok(txid) => Ok(Redirect::to(format!("/transaction/{}", txid))),
+        Err(e) => Err(renderer.render("broadcast", BroadcastInfo::new(form.data.clone(), Some(e.to_string()), check), &albatross)),
+    }
+}
+
+/// Broadcasts the transaction in the request body. Binary bodies (`application/octet-stream`)
+/// are read as beserial, anything else as hex.
+#[post("/broadcast", data = "<data>")]
+pub fn api_broadcast(data: Data, content_type: Option<&ContentType>, albatross: State<Albatross>) -> Result<Json<TransactionCheckInfo>, BadRequest<Json<BroadcastInfo>>> {
+    api_broadcast_or_check(data, content_type, false, &albatross)
+}
+
+/// Checks the transaction in the request body against the current state, without broadcasting
+/// it.
+#[post("/check", data = "<data>")]
+pub fn api_check(data: Data, content_type: Option<&ContentType>, albatross: State<Albatross>) -> Result<Json<TransactionCheckInfo>, BadRequest<Json<BroadcastInfo>>> {
+    api_broadcast_or_check(data, content_type, true, &albatross)
+}
+
+fn api_broadcast_or_check(data: Data, content_type: Option<&ContentType>, dry_run: bool, albatross: &Albatross) -> Result<Json<TransactionCheckInfo>, BadRequest<Json<BroadcastInfo>>> {
+    let is_binary = content_type.map(|content_type| content_type == &ContentType::Binary).unwrap_or(false);
+
+    let mut body = Vec::new();
+    let decoded = data.open().take(MAX_TRANSACTION_SIZE).read_to_end(&mut body)
+        .map_err(DecodeError::from)
+        .and_then(|_| if is_binary { Ok(body.clone()) } else { decode_hex(&String::from_utf8_lossy(&body)) });
+    let input = if is_binary { hex::encode(&body) } else { String::from_utf8_lossy(&body).trim().to_string() };
+
+    let (check, result) = match decoded {
+        Ok(data) => broadcast(&data, dry_run, albatross),
+        Err(e) => (None, Err(e.into())),
+    };
+
+    match (check, result) {
+        (Some(check), Ok(_)) => Ok(Json(check)),
+        (check, result) => Err(BadRequest(Some(Json(BroadcastInfo::new(
+            input,
+            result.err().map(|e| e.to_string()),
+            check,
+        ))))),
+    }
+}
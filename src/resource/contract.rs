@@ -0,0 +1,100 @@
+use serde::Serialize;
+use rocket::State;
+use rocket::http::RawStr;
+use rocket::request::{Form, FromFormValue};
+use rocket_contrib::json::Json;
+
+use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
+
+use crate::albatross::{Albatross, AccountIdentifier, ParseError};
+use crate::error::Error;
+use crate::resource::metadata::{ContractMeta, ContractPartyMeta, parse_address, parse_hash};
+use crate::utils::{serialize_with_format, serialize_address, short_hash};
+
+
+/// Role of an address in a contract
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractRole {
+    /// Sent the transaction creating the contract
+    Creator,
+    /// Owner of a vesting contract
+    Owner,
+    /// Sender of an HTLC, who can get a refund after the timeout
+    HtlcSender,
+    /// Recipient of an HTLC, who can redeem it with the pre-image
+    HtlcRecipient,
+}
+
+impl ContractRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContractRole::Creator => "creator",
+            ContractRole::Owner => "owner",
+            ContractRole::HtlcSender => "htlc_sender",
+            ContractRole::HtlcRecipient => "htlc_recipient",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "creator" => Some(ContractRole::Creator),
+            "owner" => Some(ContractRole::Owner),
+            "htlc_sender" => Some(ContractRole::HtlcSender),
+            "htlc_recipient" => Some(ContractRole::HtlcRecipient),
+            _ => None,
+        }
+    }
+}
+
+impl<'v> FromFormValue<'v> for ContractRole {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<Self, Self::Error> {
+        ContractRole::from_str(form_value.as_str()).ok_or(form_value)
+    }
+}
+
+/// A contract an address is a party of
+#[derive(Clone, Debug, Serialize)]
+pub struct ContractInfo {
+    #[serde(serialize_with = "serialize_address")]
+    address: Address,
+
+    /// `htlc` or `vesting`
+    contract_type: String,
+
+    role: ContractRole,
+
+    #[serde(serialize_with = "serialize_with_format")]
+    creation_txid: Blake2bHash,
+    short_creation_txid: String,
+    creation_block_number: u32,
+}
+
+impl ContractInfo {
+    pub fn new(contract_meta: ContractMeta, party_meta: &ContractPartyMeta) -> Result<Self, Error> {
+        let creation_txid = parse_hash(&contract_meta.creation_txid)?;
+        Ok(Self {
+            address: parse_address(&contract_meta.address)?,
+            contract_type: contract_meta.contract_type,
+            role: ContractRole::from_str(&party_meta.role)
+                .ok_or_else(|| Error::InvalidData(format!("Invalid contract role: {}", party_meta.role)))?,
+            short_creation_txid: short_hash(&creation_txid),
+            creation_txid,
+            creation_block_number: contract_meta.creation_block_number as u32,
+        })
+    }
+}
+
+#[derive(FromForm)]
+pub struct ContractParams {
+    role: Option<ContractRole>,
+}
+
+#[get("/account/<ident>/contracts?<params..>")]
+pub fn api_get_account_contracts(ident: Result<AccountIdentifier, ParseError>, params: Form<ContractParams>, albatross: State<Albatross>) -> Result<Json<Vec<ContractInfo>>, Error> {
+    let ident = ident?;
+    albatross.get_account_contracts(&ident.into(), params.role).map(Json)
+}
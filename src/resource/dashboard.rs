@@ -4,6 +4,7 @@ use rocket_contrib::templates::Template;
 use rocket::State;
 
 use crate::albatross::Albatross;
+use crate::error::Error;
 use crate::resource::ResourceRenderer;
 use crate::resource::block::BlockInfo;
 
@@ -12,14 +13,16 @@ use crate::resource::block::BlockInfo;
 pub struct DashboardInfo {
     head: BlockInfo,
     num_peers: usize,
+    consensus_established: bool,
 }
 
 #[get("/")]
-pub fn get_dashboard(albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, ()> {
-    let head = albatross.get_head_info();
+pub fn get_dashboard(albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, Error> {
+    let head = albatross.get_head_info()?;
     let info = DashboardInfo {
         head,
-        num_peers: 0,
+        num_peers: albatross.num_peers(),
+        consensus_established: albatross.is_consensus_established(),
     };
     Ok(renderer.render("dashboard", info, &albatross))
 }
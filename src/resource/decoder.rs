@@ -0,0 +1,240 @@
+use std::io::Read;
+
+use serde::Serialize;
+use rocket::State;
+use rocket::Data;
+use rocket::http::{ContentType, RawStr};
+use rocket::request::{Form, FromFormValue, FromParam};
+use rocket::response::status::BadRequest;
+use rocket_contrib::templates::Template;
+use rocket_contrib::json::Json;
+
+use nimiq_block_albatross::{Block, BlockHeader};
+use nimiq_blockchain_albatross::chain_info::ChainInfo;
+use nimiq_transaction::Transaction;
+use nimiq_account::Account;
+use nimiq_keys::Address;
+use beserial::{Deserialize, SerializingError};
+
+use crate::albatross::Albatross;
+use crate::resource::ResourceRenderer;
+use crate::resource::block::BlockInfo;
+use crate::resource::transaction::TransactionInfo;
+use crate::resource::account::AccountInfo;
+use crate::error::Error;
+
+
+/// Maximum size of uploaded data
+const MAX_DECODE_SIZE: u64 = 10 * 1024 * 1024;
+
+
+/// What the data should be decoded as
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecodeKind {
+    Block,
+    Header,
+    Transaction,
+    Account,
+}
+
+impl DecodeKind {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "block" => Some(DecodeKind::Block),
+            "header" => Some(DecodeKind::Header),
+            "transaction" => Some(DecodeKind::Transaction),
+            "account" => Some(DecodeKind::Account),
+            _ => None,
+        }
+    }
+}
+
+impl<'v> FromFormValue<'v> for DecodeKind {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<Self, Self::Error> {
+        DecodeKind::from_str(form_value.as_str()).ok_or(form_value)
+    }
+}
+
+impl<'a> FromParam<'a> for DecodeKind {
+    type Error = &'a RawStr;
+
+    fn from_param(param: &'a RawStr) -> Result<Self, Self::Error> {
+        DecodeKind::from_str(param.as_str()).ok_or(param)
+    }
+}
+
+#[derive(Debug, Fail)]
+pub enum DecodeError {
+    #[fail(display = "Invalid hex: {}", _0)]
+    InvalidHex(hex::FromHexError),
+    #[fail(display = "Failed to deserialize: {}", _0)]
+    Deserialize(SerializingError),
+    #[fail(display = "Data has {} trailing bytes after the decoded object", _0)]
+    TrailingBytes(usize),
+    #[fail(display = "Block has no body. Decode it as a header instead.")]
+    MissingBody,
+    #[fail(display = "Failed to read data: {}", _0)]
+    Io(std::io::Error),
+    #[fail(display = "Failed to get block info: {}", _0)]
+    BlockInfo(Error),
+}
+
+impl From<hex::FromHexError> for DecodeError {
+    fn from(e: hex::FromHexError) -> Self {
+        DecodeError::InvalidHex(e)
+    }
+}
+
+impl From<SerializingError> for DecodeError {
+    fn from(e: SerializingError) -> Self {
+        DecodeError::Deserialize(e)
+    }
+}
+
+impl From<std::io::Error> for DecodeError {
+    fn from(e: std::io::Error) -> Self {
+        DecodeError::Io(e)
+    }
+}
+
+/// Decoded object, rendered with the same view as objects from the chain
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", content = "content", rename_all = "snake_case")]
+pub enum Decoded {
+    Block(BlockInfo),
+    Header(BlockInfo),
+    Transaction(TransactionInfo),
+    Account(AccountInfo),
+}
+
+impl Decoded {
+    /// Name of the template used to render the decoded object
+    fn template(&self) -> &'static str {
+        match self {
+            Decoded::Block(_) | Decoded::Header(_) => "block",
+            Decoded::Transaction(_) => "transaction",
+            Decoded::Account(_) => "account",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DecodeErrorInfo {
+    error: String,
+}
+
+/// The decoder form, with the input and error of the last attempt
+#[derive(Clone, Debug, Serialize)]
+pub struct DecoderInfo {
+    kind: Option<DecodeKind>,
+    data: String,
+    error: Option<String>,
+}
+
+/// Decodes hex, ignoring whitespace and an optional `0x` prefix
+pub fn decode_hex(s: &str) -> Result<Vec<u8>, DecodeError> {
+    let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    let s = s.trim_start_matches("0x");
+    Ok(hex::decode(s)?)
+}
+
+/// Deserializes the whole input, which must not have any bytes left over
+pub fn deserialize_exact<T: Deserialize>(data: &[u8]) -> Result<T, DecodeError> {
+    let mut reader = data;
+    let value = Deserialize::deserialize(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(DecodeError::TrailingBytes(reader.len()));
+    }
+    Ok(value)
+}
+
+/// Decodes beserial `data` as `kind`. Accounts are not serialized with their address, so they're
+/// shown with the zero address.
+pub fn decode(kind: DecodeKind, data: &[u8], albatross: &Albatross) -> Result<Decoded, DecodeError> {
+    Ok(match kind {
+        DecodeKind::Block => {
+            let block: Block = deserialize_exact(data)?;
+            let has_body = match &block {
+                Block::Macro(macro_block) => macro_block.extrinsics.is_some(),
+                Block::Micro(micro_block) => micro_block.extrinsics.is_some(),
+            };
+            if !has_body {
+                return Err(DecodeError::MissingBody);
+            }
+            let block_info = albatross.get_block_info_from_chain_info(ChainInfo::initial(block))
+                .map_err(DecodeError::BlockInfo)?;
+            Decoded::Block(block_info)
+        },
+        DecodeKind::Header => {
+            let header: BlockHeader = deserialize_exact(data)?;
+            Decoded::Header(BlockInfo::from(header))
+        },
+        DecodeKind::Transaction => {
+            let transaction: Transaction = deserialize_exact(data)?;
+            Decoded::Transaction(TransactionInfo::from(transaction))
+        },
+        DecodeKind::Account => {
+            let account: Account = deserialize_exact(data)?;
+            Decoded::Account(AccountInfo::new(Address::default(), account, None, vec![], None, None, albatross.block_number()))
+        },
+    })
+}
+
+
+#[derive(FromForm)]
+pub struct DecodeForm {
+    kind: DecodeKind,
+    data: String,
+}
+
+#[get("/decode")]
+pub fn get_decoder(albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Template {
+    renderer.render("decoder", DecoderInfo {
+        kind: None,
+        data: String::new(),
+        error: None,
+    }, &albatross)
+}
+
+#[post("/decode", data = "<form>")]
+pub fn post_decoder(form: Form<DecodeForm>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Template {
+    let result = decode_hex(&form.data)
+        .and_then(|data| decode(form.kind, &data, &albatross));
+
+    match result {
+        Ok(decoded) => {
+            let template = decoded.template();
+            match decoded {
+                Decoded::Block(info) | Decoded::Header(info) => renderer.render(template, info, &albatross),
+                Decoded::Transaction(info) => renderer.render(template, info, &albatross),
+                Decoded::Account(info) => renderer.render(template, info, &albatross),
+            }
+        },
+        Err(e) => renderer.render("decoder", DecoderInfo {
+            kind: Some(form.kind),
+            data: form.data.clone(),
+            error: Some(e.to_string()),
+        }, &albatross),
+    }
+}
+
+/// Decodes the request body. Binary bodies (`application/octet-stream`) are read as beserial,
+/// anything else as hex.
+#[post("/decode/<kind>", data = "<data>")]
+pub fn api_decode(kind: DecodeKind, data: Data, content_type: Option<&ContentType>, albatross: State<Albatross>) -> Result<Json<Decoded>, BadRequest<Json<DecodeErrorInfo>>> {
+    let is_binary = content_type.map(|content_type| content_type == &ContentType::Binary).unwrap_or(false);
+
+    let mut body = Vec::new();
+    let result = data.open().take(MAX_DECODE_SIZE).read_to_end(&mut body)
+        .map_err(DecodeError::from)
+        .and_then(|_| if is_binary { Ok(body) } else { decode_hex(&String::from_utf8_lossy(&body)) })
+        .and_then(|data| decode(kind, &data, &albatross));
+
+    result.map(Json)
+        .map_err(|e| BadRequest(Some(Json(DecodeErrorInfo {
+            error: e.to_string(),
+        }))))
+}
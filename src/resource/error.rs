@@ -1,10 +1,13 @@
 use serde::Serialize;
-use rocket::http::Status;
+use rocket::http::{RawStr, Status};
+use rocket::http::uri::Uri;
 use rocket::request::Request;
+use rocket::response::{self, Response, Responder};
 use rocket::State;
-use rocket_contrib::templates::Template;
+use rocket_contrib::json::Json;
 
 use crate::albatross::Albatross;
+use crate::error::Error;
 use crate::resource::ResourceRenderer;
 
 
@@ -13,21 +16,130 @@ pub struct ErrorInfo {
     code: u16,
     name: String,
     description: String,
+
+    /// Corrected identifier, for identifiers that failed to parse, and the URL with the identifier
+    /// replaced by it
+    suggestion: Option<String>,
+    suggestion_url: Option<String>,
+
+    /// Identifier that failed to parse
+    #[serde(skip)]
+    input: Option<String>,
 }
 
-impl From<Status> for ErrorInfo {
-    fn from(status: Status) -> Self {
+impl ErrorInfo {
+    pub fn new<S: Into<String>>(status: Status, description: S) -> Self {
         Self {
             code: status.code,
             name: status.reason.to_string(),
-            description: "".to_string()
+            description: description.into(),
+            suggestion: None,
+            suggestion_url: None,
+            input: None,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        Status::from_code(self.code).unwrap_or(Status::InternalServerError)
+    }
+}
+
+impl From<Status> for ErrorInfo {
+    fn from(status: Status) -> Self {
+        let description = match status.code {
+            400 => "The request is malformed.",
+            404 => "The page or object you're looking for doesn't exist.",
+            503 => "The explorer can't serve this right now. Please try again later.",
+            _ => "Something went wrong on our side.",
+        };
+        Self::new(status, description)
+    }
+}
+
+/// Server errors only get the generic description, their details are logged
+impl From<&Error> for ErrorInfo {
+    fn from(error: &Error) -> Self {
+        let status = error.status();
+        if status.code >= 500 {
+            return Self::from(status);
+        }
+        let mut info = Self::new(status, error.to_string());
+        if let Error::Parse(e) = error {
+            info.input = e.input().map(String::from);
+            info.suggestion = e.suggestion().map(String::from);
         }
+        info
     }
 }
 
+/// Replaces the path segment that is `input` with `suggestion`
+fn suggestion_url(path: &str, input: &str, suggestion: &str) -> Option<String> {
+    let mut found = false;
+    let segments = path.split('/')
+        .map(|segment| match RawStr::from_str(segment).url_decode() {
+            Ok(ref decoded) if decoded.as_str() == input => {
+                found = true;
+                Uri::percent_encode(suggestion).into_owned()
+            },
+            _ => segment.to_string(),
+        })
+        .collect::<Vec<String>>();
+    if found { Some(segments.join("/")) } else { None }
+}
+
+/// Responds with JSON to API requests and clients that prefer JSON, and renders the error page
+/// otherwise.
+impl<'r> Responder<'r> for ErrorInfo {
+    fn respond_to(mut self, request: &Request) -> response::Result<'r> {
+        let status = self.status();
+        if let (Some(input), Some(suggestion)) = (&self.input, &self.suggestion) {
+            self.suggestion_url = suggestion_url(request.uri().path(), input, suggestion);
+        }
+        let wants_json = request.uri().path().starts_with("/api/")
+            || request.accept().map(|accept| accept.preferred().is_json()).unwrap_or(false);
+
+        let renderer = request.guard::<State<ResourceRenderer>>().succeeded();
+        let albatross = request.guard::<State<Albatross>>().succeeded();
+        let response = match (wants_json, renderer, albatross) {
+            (false, Some(renderer), Some(albatross)) => renderer.render("error", self, &albatross).respond_to(request)?,
+            _ => Json(self).respond_to(request)?,
+        };
+
+        Response::build_from(response)
+            .status(status)
+            .ok()
+    }
+}
+
+#[catch(400)]
+pub fn bad_request(_request: &Request) -> ErrorInfo {
+    ErrorInfo::from(Status::BadRequest)
+}
+
 #[catch(404)]
-pub fn not_found(request: &Request) -> Template {
-    let renderer = request.guard::<State<ResourceRenderer>>().expect("Missing renderer");
-    let albatross = request.guard::<State<Albatross>>().expect("Missing Albatross");
-    renderer.render("error", ErrorInfo::from(Status::NotFound), &albatross)
+pub fn not_found(_request: &Request) -> ErrorInfo {
+    ErrorInfo::from(Status::NotFound)
+}
+
+#[catch(500)]
+pub fn internal_error(_request: &Request) -> ErrorInfo {
+    ErrorInfo::from(Status::InternalServerError)
+}
+
+#[catch(503)]
+pub fn service_unavailable(_request: &Request) -> ErrorInfo {
+    ErrorInfo::from(Status::ServiceUnavailable)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestion_url_replaces_input_segment() {
+        assert_eq!(suggestion_url("/block/0xabcd", "0xabcd", "abcd"), Some("/block/abcd".to_string()));
+        assert_eq!(suggestion_url("/account/NQ08%200000", "NQ08 0000", "NQ07 0000"), Some("/account/NQ07%200000".to_string()));
+        assert_eq!(suggestion_url("/block/1234", "0xabcd", "abcd"), None);
+    }
 }
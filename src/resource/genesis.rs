@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 
 use serde::Serialize;
 use rocket::State;
@@ -11,6 +12,7 @@ use nimiq_account::Account;
 
 use crate::resource::ResourceRenderer;
 use crate::albatross::Albatross;
+use crate::error::Error;
 use crate::utils::serialize_with_format;
 use nimiq_primitives::networks::NetworkId;
 
@@ -30,27 +32,31 @@ pub struct GenesisInfo {
     pub accounts: BTreeMap<Address, Account>,
 }
 
-impl From<&NetworkInfo> for GenesisInfo {
-    fn from(network_info: &NetworkInfo) -> Self {
+impl TryFrom<&NetworkInfo> for GenesisInfo {
+    type Error = Error;
+
+    fn try_from(network_info: &NetworkInfo) -> Result<Self, Error> {
         let mut accounts = BTreeMap::new();
         for (address, account) in network_info.genesis_accounts() {
             accounts.insert(address, account);
         }
 
-        Self {
+        Ok(Self {
             genesis_hash: network_info.genesis_hash().clone(),
             network_id: network_info.network_id(),
             staking_contract: network_info.validator_registry_address()
-                .expect("Expected validator registry address to be set")
+                .ok_or_else(|| Error::InvalidGenesis("Validator registry address is not set".to_string()))?
                 .clone(),
             accounts,
-        }
+        })
     }
 }
 
-impl From<NetworkInfo> for GenesisInfo {
-    fn from(network_info: NetworkInfo) -> Self {
-        Self::from(&network_info)
+impl TryFrom<NetworkInfo> for GenesisInfo {
+    type Error = Error;
+
+    fn try_from(network_info: NetworkInfo) -> Result<Self, Error> {
+        Self::try_from(&network_info)
     }
 }
 
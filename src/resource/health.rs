@@ -0,0 +1,94 @@
+use serde::Serialize;
+use chrono::{DateTime, Utc};
+use rocket::State;
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket_contrib::json::Json;
+
+use crate::albatross::Albatross;
+use crate::error::Error;
+use crate::utils::serialize_datetime;
+
+
+/// Head blocks older than this mean we're stuck
+const MAX_HEAD_AGE_SECS: i64 = 5 * 60;
+
+/// Maximum number of blocks the indexer may be behind the head
+const MAX_INDEXER_LAG: u32 = 10;
+
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HealthInfo {
+    healthy: bool,
+
+    /// Running offline over a stored chain. The head isn't expected to advance then.
+    offline: bool,
+
+    consensus_established: bool,
+
+    head_block_number: u32,
+    #[serde(serialize_with = "serialize_datetime")]
+    head_timestamp: DateTime<Utc>,
+    head_age_secs: i64,
+
+    /// Highest indexed block and how far that is behind the head
+    indexed_block_number: Option<u32>,
+    pub indexer_lag: Option<u32>,
+
+    pub database_reachable: bool,
+}
+
+impl HealthInfo {
+    pub fn new(offline: bool, consensus_established: bool, head_block_number: u32, head_timestamp: DateTime<Utc>, indexed_block_number: Option<u32>, database_reachable: bool) -> Self {
+        let head_age_secs = (Utc::now() - head_timestamp).num_seconds();
+        let indexer_lag = indexed_block_number
+            .map(|indexed| head_block_number.saturating_sub(indexed));
+
+        let healthy = (offline || (consensus_established && head_age_secs <= MAX_HEAD_AGE_SECS))
+            && database_reachable
+            && indexer_lag.map(|lag| lag <= MAX_INDEXER_LAG).unwrap_or(false);
+
+        Self {
+            healthy,
+            offline,
+            consensus_established,
+            head_block_number,
+            head_timestamp,
+            head_age_secs,
+            indexed_block_number,
+            indexer_lag,
+            database_reachable,
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.healthy
+    }
+
+    /// Warning shown in a banner on every page. Only covers what affects the data we show.
+    pub fn warning(&self) -> Option<String> {
+        if !self.database_reachable {
+            Some("Database is not reachable".to_string())
+        }
+        else if self.offline {
+            Some("Offline: serving a stored chain without connecting to the network".to_string())
+        }
+        else if !self.consensus_established {
+            Some("Not in consensus with the network. Data might be outdated.".to_string())
+        }
+        else {
+            match self.indexer_lag {
+                Some(lag) if lag > MAX_INDEXER_LAG => Some(format!("Indexer is {} blocks behind. Transactions and accounts might be incomplete.", lag)),
+                None => Some("Indexer hasn't indexed any blocks yet".to_string()),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[get("/health")]
+pub fn get_health(albatross: State<Albatross>) -> Result<Custom<Json<HealthInfo>>, Error> {
+    let health = albatross.get_health_info()?;
+    let status = if health.is_healthy() { Status::Ok } else { Status::ServiceUnavailable };
+    Ok(Custom(status, Json(health)))
+}
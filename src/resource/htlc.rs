@@ -0,0 +1,217 @@
+use std::convert::TryFrom;
+
+use serde::Serialize;
+use rocket::State;
+use rocket::http::RawStr;
+use rocket::request::{Form, FromFormValue};
+use rocket_contrib::templates::Template;
+use rocket_contrib::json::Json;
+
+use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
+use nimiq_primitives::coin::Coin;
+
+use crate::albatross::Albatross;
+use crate::error::Error;
+use crate::resource::ResourceRenderer;
+use crate::resource::metadata::{HtlcMeta, HtlcEventMeta, parse_address, parse_hash};
+use crate::utils::{serialize_with_format, serialize_address, short_hash};
+
+
+/// Kind of an HTLC event, as stored in the database
+pub const HTLC_EVENT_FUNDING: &str = "funding";
+pub const HTLC_EVENT_REGULAR_TRANSFER: &str = "regular_transfer";
+pub const HTLC_EVENT_EARLY_RESOLVE: &str = "early_resolve";
+pub const HTLC_EVENT_TIMEOUT_RESOLVE: &str = "timeout_resolve";
+
+const DEFAULT_HTLCS_PER_PAGE: u32 = 50;
+const MAX_HTLCS_PER_PAGE: u32 = 500;
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HtlcState {
+    /// Not redeemed yet and the timeout hasn't passed
+    Pending,
+    /// Funds were redeemed by the recipient, or resolved early
+    Redeemed,
+    /// Timeout has passed without the funds being redeemed
+    TimedOut,
+}
+
+impl<'v> FromFormValue<'v> for HtlcState {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<Self, Self::Error> {
+        match form_value.as_str() {
+            "pending" => Ok(HtlcState::Pending),
+            "redeemed" => Ok(HtlcState::Redeemed),
+            "timed_out" => Ok(HtlcState::TimedOut),
+            _ => Err(form_value),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HtlcEventInfo {
+    #[serde(serialize_with = "serialize_with_format")]
+    txid: Blake2bHash,
+    short_txid: String,
+    block_number: u32,
+
+    /// `funding`, `regular_transfer`, `early_resolve` or `timeout_resolve`
+    kind: String,
+    is_funding: bool,
+
+    /// Value that was added to or taken from the contract, including fees
+    #[serde(serialize_with = "serialize_with_format")]
+    amount: Coin,
+
+    /// Pre-image revealed to redeem the funds
+    pre_image: Option<String>,
+}
+
+impl TryFrom<HtlcEventMeta> for HtlcEventInfo {
+    type Error = Error;
+
+    fn try_from(meta: HtlcEventMeta) -> Result<Self, Error> {
+        let txid = parse_hash(&meta.txid)?;
+        Ok(Self {
+            short_txid: short_hash(&txid),
+            txid,
+            block_number: meta.block_number as u32,
+            is_funding: meta.kind == HTLC_EVENT_FUNDING,
+            kind: meta.kind,
+            amount: Coin::from_u64_unchecked(meta.amount as u64),
+            pre_image: meta.pre_image,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HtlcInfo {
+    #[serde(serialize_with = "serialize_address")]
+    address: Address,
+
+    #[serde(serialize_with = "serialize_with_format")]
+    creation_txid: Blake2bHash,
+    short_creation_txid: String,
+    creation_block_number: u32,
+
+    #[serde(serialize_with = "serialize_address")]
+    sender: Address,
+    #[serde(serialize_with = "serialize_address")]
+    recipient: Address,
+
+    hash_algorithm: String,
+    hash_root: String,
+    hash_count: u8,
+    timeout: u32,
+
+    /// Amount funded, including later fundings
+    #[serde(serialize_with = "serialize_with_format")]
+    total_amount: Coin,
+
+    /// Amount taken out of the contract so far
+    #[serde(serialize_with = "serialize_with_format")]
+    redeemed_amount: Coin,
+
+    pub state: HtlcState,
+
+    /// Blocks left until the timeout, if it hasn't passed yet
+    blocks_until_timeout: Option<u32>,
+
+    events: Vec<HtlcEventInfo>,
+}
+
+impl HtlcInfo {
+    pub fn new(meta: HtlcMeta, events: Vec<HtlcEventMeta>, block_number: u32) -> Result<Self, Error> {
+        let creation_txid = parse_hash(&meta.creation_txid)?;
+        let timeout = meta.timeout as u32;
+
+        let events: Vec<HtlcEventInfo> = events.into_iter()
+            .map(HtlcEventInfo::try_from)
+            .collect::<Result<_, Error>>()?;
+
+        let total_amount = meta.total_amount as u64;
+
+        let redeemed_amount: u64 = events.iter()
+            .filter(|event| !event.is_funding)
+            .map(|event| u64::from(event.amount))
+            .sum();
+        let is_redeemed = events.iter()
+            .any(|event| event.kind == HTLC_EVENT_REGULAR_TRANSFER || event.kind == HTLC_EVENT_EARLY_RESOLVE);
+
+        let state = if is_redeemed && redeemed_amount >= total_amount {
+            HtlcState::Redeemed
+        }
+        else if block_number >= timeout {
+            HtlcState::TimedOut
+        }
+        else {
+            HtlcState::Pending
+        };
+
+        Ok(Self {
+            address: parse_address(&meta.address)?,
+            short_creation_txid: short_hash(&creation_txid),
+            creation_txid,
+            creation_block_number: meta.creation_block_number as u32,
+            sender: parse_address(&meta.sender)?,
+            recipient: parse_address(&meta.recipient)?,
+            hash_algorithm: meta.hash_algorithm,
+            hash_root: meta.hash_root,
+            hash_count: meta.hash_count as u8,
+            timeout,
+            total_amount: Coin::from_u64_unchecked(total_amount),
+            redeemed_amount: Coin::from_u64_unchecked(redeemed_amount),
+            state,
+            blocks_until_timeout: if block_number < timeout { Some(timeout - block_number) } else { None },
+            events,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HtlcListInfo {
+    state: Option<HtlcState>,
+    htlcs: Vec<HtlcInfo>,
+
+    /// Offsets of the previous and next page, if there are any
+    prev_offset: Option<u32>,
+    next_offset: Option<u32>,
+}
+
+#[derive(FromForm)]
+pub struct HtlcListParams {
+    state: Option<HtlcState>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+impl HtlcListParams {
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_HTLCS_PER_PAGE).min(MAX_HTLCS_PER_PAGE)
+    }
+
+    fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+}
+
+#[get("/htlcs?<params..>")]
+pub fn get_htlcs(params: Form<HtlcListParams>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, Error> {
+    let (limit, offset) = (params.limit(), params.offset());
+    let htlcs = albatross.get_htlc_infos(params.state, limit, offset)?;
+    Ok(renderer.render("htlcs", HtlcListInfo {
+        state: params.state,
+        prev_offset: if offset > 0 { Some(offset.saturating_sub(limit)) } else { None },
+        next_offset: if htlcs.len() as u32 == limit { Some(offset + limit) } else { None },
+        htlcs,
+    }, &albatross))
+}
+
+#[get("/htlcs?<params..>")]
+pub fn api_get_htlcs(params: Form<HtlcListParams>, albatross: State<Albatross>) -> Result<Json<Vec<HtlcInfo>>, Error> {
+    albatross.get_htlc_infos(params.state, params.limit(), params.offset()).map(Json)
+}
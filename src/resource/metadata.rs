@@ -1,17 +1,35 @@
 use std::fmt::Debug;
 use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 
 use diesel::prelude::*;
 use diesel::pg::PgConnection;
 use diesel::r2d2::ConnectionManager;
+use diesel::dsl::{sql, not};
+use diesel::sql_types::{Text, BigInt, Bool};
 use r2d2::{Pool, PooledConnection};
 
 use nimiq_block_albatross::{Block, ForkProof};
-use nimiq_transaction::Transaction;
+use nimiq_transaction::{Transaction, TransactionFlags};
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::Address;
-
-use crate::schema::{transactions, account_aliases};
+use nimiq_primitives::account::AccountType;
+use nimiq_bls::bls12_381::CompressedPublicKey;
+use nimiq_transaction::account::staking_contract::StakingTransactionData;
+use nimiq_account::staking_contract::StakingContract;
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::policy::epoch_at;
+use beserial::Deserialize;
+
+use crate::schema::{transactions, account_aliases, stakes, epoch_rewards, transaction_messages, htlcs, htlc_events,
+                    contracts, contract_parties, blocks};
+use crate::resource::rewards::EpochRewardInfo;
+use crate::resource::transaction_data::TransactionData;
+use crate::resource::transaction_proof::TransactionProof;
+use crate::resource::contract::ContractRole;
+use crate::resource::htlc::{HtlcState, HTLC_EVENT_FUNDING, HTLC_EVENT_REGULAR_TRANSFER, HTLC_EVENT_EARLY_RESOLVE, HTLC_EVENT_TIMEOUT_RESOLVE};
+use crate::utils::printable_utf8;
+use crate::error::Error;
 
 
 
@@ -51,6 +69,222 @@ struct NewTransactionMeta {
     recipient: String,
 }
 
+#[derive(Clone, Debug, Queryable)]
+pub struct StakeMeta {
+    id: i32,
+    pub staker_address: String,
+    pub reward_address: String,
+    pub validator_key: String,
+    pub block_number: i32,
+}
+
+#[derive(Clone, Debug, Insertable, AsChangeset)]
+#[table_name="stakes"]
+struct NewStakeMeta {
+    staker_address: String,
+    reward_address: String,
+    validator_key: String,
+    block_number: i32,
+}
+
+impl NewStakeMeta {
+    fn new(staker_address: &Address, reward_address: Option<&Address>, validator_key: &CompressedPublicKey, block_number: u32) -> Self {
+        Self {
+            staker_address: staker_address.to_user_friendly_address(),
+            // stakers without reward address receive the rewards themselves
+            reward_address: reward_address.unwrap_or(staker_address).to_user_friendly_address(),
+            validator_key: validator_key.to_hex(),
+            block_number: block_number as i32,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Queryable)]
+pub struct EpochRewardMeta {
+    id: i32,
+    pub epoch: i32,
+    pub block_hash: String,
+    pub staker_address: String,
+    pub reward_address: String,
+    pub num_slots: i32,
+    pub num_slashed: i32,
+    pub reward: i64,
+    pub slash_deduction: i64,
+    pub payout: i64,
+}
+
+impl TryFrom<EpochRewardMeta> for EpochRewardInfo {
+    type Error = Error;
+
+    fn try_from(meta: EpochRewardMeta) -> Result<Self, Error> {
+        Ok(EpochRewardInfo::new(
+            meta.epoch as u32,
+            parse_hash(&meta.block_hash)?,
+            parse_address(&meta.staker_address)?,
+            parse_address(&meta.reward_address)?,
+            meta.num_slots as u16,
+            meta.num_slashed as u16,
+            Coin::from_u64_unchecked(meta.reward as u64),
+            Coin::from_u64_unchecked(meta.slash_deduction as u64),
+        ))
+    }
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[table_name="epoch_rewards"]
+struct NewEpochRewardMeta {
+    epoch: i32,
+    block_hash: String,
+    staker_address: String,
+    reward_address: String,
+    num_slots: i32,
+    num_slashed: i32,
+    reward: i64,
+    slash_deduction: i64,
+    payout: i64,
+}
+
+impl From<&EpochRewardInfo> for NewEpochRewardMeta {
+    fn from(info: &EpochRewardInfo) -> Self {
+        Self {
+            epoch: info.epoch as i32,
+            block_hash: info.block_hash.to_hex(),
+            staker_address: info.staker_address.to_user_friendly_address(),
+            reward_address: info.reward_address.to_user_friendly_address(),
+            num_slots: i32::from(info.num_slots),
+            num_slashed: i32::from(info.num_slashed),
+            reward: u64::from(info.reward) as i64,
+            slash_deduction: u64::from(info.slash_deduction) as i64,
+            payout: u64::from(info.payout) as i64,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Queryable, QueryableByName)]
+#[table_name="transaction_messages"]
+pub struct TransactionMessageMeta {
+    id: i32,
+    pub txid: String,
+    pub block_number: i32,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[table_name="transaction_messages"]
+struct NewTransactionMessageMeta {
+    txid: String,
+    block_number: i32,
+    message: String,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[table_name="blocks"]
+struct NewBlockMeta {
+    block_hash: String,
+    block_number: i32,
+    is_macro: bool,
+    num_transactions: i32,
+}
+
+#[derive(Clone, Debug, Queryable)]
+pub struct HtlcMeta {
+    id: i32,
+    pub address: String,
+    pub creation_txid: String,
+    pub creation_block_number: i32,
+    pub sender: String,
+    pub recipient: String,
+    pub hash_algorithm: String,
+    pub hash_root: String,
+    pub hash_count: i32,
+    pub timeout: i32,
+    pub total_amount: i64,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[table_name="htlcs"]
+struct NewHtlcMeta {
+    address: String,
+    creation_txid: String,
+    creation_block_number: i32,
+    sender: String,
+    recipient: String,
+    hash_algorithm: String,
+    hash_root: String,
+    hash_count: i32,
+    timeout: i32,
+    total_amount: i64,
+}
+
+#[derive(Clone, Debug, Queryable)]
+pub struct HtlcEventMeta {
+    id: i32,
+    pub address: String,
+    pub txid: String,
+    pub block_number: i32,
+    pub kind: String,
+    pub amount: i64,
+    pub pre_image: Option<String>,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[table_name="htlc_events"]
+struct NewHtlcEventMeta {
+    address: String,
+    txid: String,
+    block_number: i32,
+    kind: String,
+    amount: i64,
+    pre_image: Option<String>,
+}
+
+#[derive(Clone, Debug, Queryable)]
+pub struct ContractMeta {
+    id: i32,
+    pub address: String,
+    pub contract_type: String,
+    pub creation_txid: String,
+    pub creation_block_number: i32,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[table_name="contracts"]
+struct NewContractMeta {
+    address: String,
+    contract_type: String,
+    creation_txid: String,
+    creation_block_number: i32,
+}
+
+#[derive(Clone, Debug, Queryable)]
+pub struct ContractPartyMeta {
+    id: i32,
+    pub contract_address: String,
+    pub party_address: String,
+    pub role: String,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[table_name="contract_parties"]
+struct NewContractPartyMeta {
+    contract_address: String,
+    party_address: String,
+    role: String,
+}
+
+
+/// Parses an address that was stored in the database
+pub fn parse_address(s: &str) -> Result<Address, Error> {
+    Address::from_user_friendly_address(s)
+        .map_err(|e| Error::InvalidData(format!("Invalid address {}: {}", s, e)))
+}
+
+/// Parses a hex-encoded hash that was stored in the database
+pub fn parse_hash(s: &str) -> Result<Blake2bHash, Error> {
+    s.parse::<Blake2bHash>()
+        .map_err(|_| Error::InvalidData(format!("Invalid hash: {}", s)))
+}
+
 
 type PgPool = Pool<ConnectionManager<PgConnection>>;
 type PgPoolConnection = PooledConnection<ConnectionManager<PgConnection>>;
@@ -62,54 +296,157 @@ type PgPoolConnection = PooledConnection<ConnectionManager<PgConnection>>;
 pub struct MetadataStore {
     db_pool: PgPool,
     account_aliases: HashMap<Address, String>,
+    staking_contract: Option<Address>,
 }
 
 impl MetadataStore {
-    pub fn new<S: AsRef<str>>(url: S) -> Self {
+    pub fn new<S: AsRef<str>>(url: S) -> Result<Self, Error> {
         // create database connection pool
         let manager = ConnectionManager::<PgConnection>::new(url.as_ref());
-        let db_pool = Pool::builder().build(manager)
-            .expect("Failed to create database connection pool");
+        let db_pool = Pool::builder().build(manager)?;
 
-        Self {
+        Ok(Self {
             db_pool,
             account_aliases: HashMap::new(),
+            staking_contract: None,
+        })
+    }
+
+    fn db_conn(&self) -> Result<PgPoolConnection, Error> {
+        Ok(self.db_pool.get()?)
+    }
+
+    /// Replaces the recorded rewards of the epoch concluded by the macro block `block`
+    pub fn finalize_epoch(&self, block: &Block, rewards: &[EpochRewardInfo]) -> Result<(), Error> {
+        if !block.is_macro() {
+            return Err(Error::NotMacroBlock(block.hash::<Blake2bHash>().to_string()));
         }
+        let epoch = epoch_at(block.block_number()) as i32;
+
+        let reward_metas: Vec<NewEpochRewardMeta> = rewards.iter()
+            .map(NewEpochRewardMeta::from)
+            .collect();
+
+        // a rebranch might finalize the same epoch again with a different block
+        let conn = self.db_conn()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(epoch_rewards::table.filter(epoch_rewards::epoch.eq(epoch)))
+                .execute(&conn)?;
+            diesel::insert_into(epoch_rewards::table)
+                .values(&reward_metas)
+                .execute(&conn)?;
+            Ok(())
+        })?;
+        Ok(())
     }
 
-    fn db_conn(&self) -> PgPoolConnection {
-        self.db_pool.get()
-            .unwrap_or_else(|e| panic!("Failed to get database connection from pool: {}", e))
+    pub fn get_epoch_rewards(&self, epoch: u32) -> Result<Vec<EpochRewardInfo>, Error> {
+        use epoch_rewards::dsl;
+        dsl::epoch_rewards
+            .filter(dsl::epoch.eq(epoch as i32))
+            .order(dsl::id.asc())
+            .load::<EpochRewardMeta>(&self.db_conn()?)?
+            .into_iter()
+            .map(EpochRewardInfo::try_from)
+            .collect()
     }
 
-    pub fn finalize_epoch(&self, _block_hash: &Blake2bHash, block: &Block) {
-        let _macro_block = block.unwrap_macro_ref();
-        // TODO
+    pub fn get_account_rewards(&self, reward_address: &Address) -> Result<Vec<EpochRewardInfo>, Error> {
+        use epoch_rewards::dsl;
+        dsl::epoch_rewards
+            .filter(dsl::reward_address.eq(reward_address.to_user_friendly_address()))
+            .order(dsl::epoch.desc())
+            .load::<EpochRewardMeta>(&self.db_conn()?)?
+            .into_iter()
+            .map(EpochRewardInfo::try_from)
+            .collect()
     }
 
-    pub fn push_block(&self, block_hash: &Blake2bHash, block: &Block) {
-        match block {
-            Block::Micro(micro_block) => {
-                let extrinsics = micro_block.extrinsics.as_ref().expect("Micro block without extrinsics");
+    /// Indexes a block. All of its rows are inserted in one DB transaction, and rows that already
+    /// exist are skipped, so a block can be indexed again.
+    pub fn push_block(&self, block_hash: &Blake2bHash, block: &Block) -> Result<(), Error> {
+        let conn = self.db_conn()?;
+        conn.transaction::<_, Error, _>(|| {
+            match block {
+                Block::Micro(micro_block) => {
+                    let extrinsics = micro_block.extrinsics.as_ref()
+                        .ok_or_else(|| Error::MissingInChain(format!("Extrinsics of block {}", block_hash)))?;
+
+                    for (i, transaction) in extrinsics.transactions.iter().enumerate() {
+                        self.push_transaction(&conn, transaction, &block_hash, micro_block.header.block_number, i)?;
+                        self.push_staking_transaction(&conn, transaction, micro_block.header.block_number)?;
+                        self.push_htlc_transaction(&conn, transaction, micro_block.header.block_number)?;
+                        self.push_contract_creation(&conn, transaction, micro_block.header.block_number)?;
+                    }
+
+                    for (i, fork_proof) in extrinsics.fork_proofs.iter().enumerate() {
+                        self.push_fork_proof(fork_proof, &block_hash, i);
+                    }
+
+                    // TODO also look into micro block justification to store information about view changes?
+                },
+
+                Block::Macro(_) => {
+                    // TODO: Has no transactions, but we could also store inherents in the DB
+                },
+            }
+
+            self.push_block_meta(&conn, block_hash, block)
+        })
+    }
 
-                for (i, transaction) in extrinsics.transactions.iter().enumerate() {
-                    self.push_transaction(transaction, &block_hash, micro_block.header.block_number, i);
-                }
+    /// Records that a block was indexed
+    fn push_block_meta(&self, conn: &PgConnection, block_hash: &Blake2bHash, block: &Block) -> Result<(), Error> {
+        let num_transactions = match block {
+            Block::Micro(micro_block) => micro_block.extrinsics.as_ref()
+                .map(|extrinsics| extrinsics.transactions.len())
+                .unwrap_or(0),
+            Block::Macro(_) => 0,
+        };
+        let block_meta = NewBlockMeta {
+            block_hash: block_hash.to_hex(),
+            block_number: block.block_number() as i32,
+            is_macro: block.is_macro(),
+            num_transactions: num_transactions as i32,
+        };
+        diesel::insert_into(blocks::table)
+            .values(&block_meta)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        Ok(())
+    }
 
-                for (i, fork_proof) in extrinsics.fork_proofs.iter().enumerate() {
-                    self.push_fork_proof(fork_proof, &block_hash, i);
-                }
+    /// Highest block number that was indexed
+    pub fn get_indexed_block_number(&self) -> Result<Option<u32>, Error> {
+        use blocks::dsl;
+        let block_number = dsl::blocks
+            .select(diesel::dsl::max(dsl::block_number))
+            .first::<Option<i32>>(&self.db_conn()?)?;
+        Ok(block_number.map(|block_number| block_number as u32))
+    }
 
-                // TODO also look into micro block justification to store information about view changes?
-            },
+    pub fn count_indexed_blocks(&self) -> Result<u64, Error> {
+        let count = blocks::table
+            .count()
+            .get_result::<i64>(&self.db_conn()?)?;
+        Ok(count as u64)
+    }
 
-            Block::Macro(_) => {
-                // TODO: Has no transactions, but we could also store inherents in the DB
-            },
-        }
+    pub fn count_indexed_transactions(&self) -> Result<u64, Error> {
+        let count = transactions::table
+            .count()
+            .get_result::<i64>(&self.db_conn()?)?;
+        Ok(count as u64)
+    }
+
+    /// Returns the number of connections in the pool, the number of idle connections and the
+    /// maximum pool size
+    pub fn pool_state(&self) -> (u32, u32, u32) {
+        let state = self.db_pool.state();
+        (state.connections, state.idle_connections, self.db_pool.max_size())
     }
 
-    fn push_transaction(&self, transaction: &Transaction, block_hash: &Blake2bHash, block_number: u32, tx_idx: usize) -> TransactionMeta {
+    fn push_transaction(&self, conn: &PgConnection, transaction: &Transaction, block_hash: &Blake2bHash, block_number: u32, tx_idx: usize) -> Result<(), Error> {
         let txid = transaction.hash::<Blake2bHash>();
 
         let tx_meta = NewTransactionMeta {
@@ -121,10 +458,304 @@ impl MetadataStore {
             recipient: transaction.recipient.to_user_friendly_address(),
         };
 
+        if let Some(message) = printable_utf8(&transaction.data) {
+            let message_meta = NewTransactionMessageMeta {
+                txid: tx_meta.txid.clone(),
+                block_number: tx_meta.block_number,
+                message,
+            };
+            diesel::insert_into(transaction_messages::table)
+                .values(&message_meta)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+        }
+
         diesel::insert_into(transactions::table)
             .values(&tx_meta)
-            .get_result(&self.db_conn())
-            .expect("Failed to write transaction meta data to database")
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Full-text search in transaction messages. Newest first.
+    pub fn search_messages(&self, query: &str, limit: i64) -> Result<Vec<TransactionMessageMeta>, Error> {
+        let message_metas = diesel::sql_query("SELECT * FROM transaction_messages \
+                           WHERE to_tsvector('simple', message) @@ plainto_tsquery('simple', $1) \
+                           ORDER BY block_number DESC LIMIT $2")
+            .bind::<Text, _>(query)
+            .bind::<BigInt, _>(limit)
+            .load::<TransactionMessageMeta>(&self.db_conn()?)?;
+        Ok(message_metas)
+    }
+
+    /// Updates the stake index, if this transaction stakes in the staking contract
+    fn push_staking_transaction(&self, conn: &PgConnection, transaction: &Transaction, block_number: u32) -> Result<(), Error> {
+        let is_stake = transaction.recipient_type == AccountType::Staking
+            && transaction.sender_type != AccountType::Staking
+            && Some(&transaction.recipient) == self.staking_contract.as_ref();
+        if !is_stake {
+            return Ok(());
+        }
+
+        let data: StakingTransactionData = match Deserialize::deserialize_from_vec(&transaction.data) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Failed to parse staking transaction data: {}", e);
+                return Ok(());
+            },
+        };
+
+        let stake_meta = NewStakeMeta::new(&transaction.sender, data.reward_address.as_ref(), &data.validator_key, block_number);
+
+        diesel::insert_into(stakes::table)
+            .values(&stake_meta)
+            .on_conflict(stakes::staker_address)
+            .do_update()
+            .set(&stake_meta)
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Replaces the stake index with the active stakes in the staking contract. This is done on
+    /// every macro block, to also catch stakes that were retired or unstaked.
+    pub fn sync_stakes(&self, block_number: u32, staking_contract: &StakingContract) -> Result<(), Error> {
+        let stake_metas: Vec<NewStakeMeta> = staking_contract.active_stake_sorted.iter()
+            .map(|active| NewStakeMeta::new(&active.staker_address, active.reward_address.as_ref(), &active.validator_key, block_number))
+            .collect();
+
+        let conn = self.db_conn()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(stakes::table).execute(&conn)?;
+            diesel::insert_into(stakes::table)
+                .values(&stake_metas)
+                .execute(&conn)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Records creation, funding and redeeming of HTLCs
+    fn push_htlc_transaction(&self, conn: &PgConnection, transaction: &Transaction, block_number: u32) -> Result<(), Error> {
+        let txid = transaction.hash::<Blake2bHash>().to_hex();
+
+        if transaction.recipient_type == AccountType::HTLC {
+            if let Some(Ok(TransactionData::HtlcCreation { contract_address, sender, recipient, hash_algorithm, hash_root, hash_count, timeout })) = TransactionData::decode(transaction) {
+                let htlc_meta = NewHtlcMeta {
+                    address: contract_address.to_user_friendly_address(),
+                    creation_txid: txid.clone(),
+                    creation_block_number: block_number as i32,
+                    sender: sender.to_user_friendly_address(),
+                    recipient: recipient.to_user_friendly_address(),
+                    hash_algorithm: hash_algorithm.to_string(),
+                    hash_root: hash_root.to_string(),
+                    hash_count: i32::from(hash_count),
+                    timeout: timeout as i32,
+                    total_amount: u64::from(transaction.value) as i64,
+                };
+                diesel::insert_into(htlcs::table)
+                    .values(&htlc_meta)
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+            }
+
+            let address = if transaction.flags.contains(TransactionFlags::CONTRACT_CREATION) {
+                transaction.contract_creation_address()
+            } else {
+                transaction.recipient.clone()
+            };
+            let event_meta = NewHtlcEventMeta {
+                address: address.to_user_friendly_address(),
+                txid: txid.clone(),
+                block_number: block_number as i32,
+                kind: HTLC_EVENT_FUNDING.to_string(),
+                amount: u64::from(transaction.value) as i64,
+                pre_image: None,
+            };
+            diesel::insert_into(htlc_events::table)
+                .values(&event_meta)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+        }
+
+        if transaction.sender_type == AccountType::HTLC {
+            let (kind, pre_image) = match TransactionProof::decode(transaction) {
+                Ok(TransactionProof::HtlcRegularTransfer { pre_image, .. }) => (HTLC_EVENT_REGULAR_TRANSFER, Some(pre_image.to_string())),
+                Ok(TransactionProof::HtlcEarlyResolve { .. }) => (HTLC_EVENT_EARLY_RESOLVE, None),
+                Ok(TransactionProof::HtlcTimeoutResolve { .. }) => (HTLC_EVENT_TIMEOUT_RESOLVE, None),
+                _ => {
+                    warn!("Failed to decode HTLC proof of transaction {}", txid);
+                    return Ok(());
+                },
+            };
+            let event_meta = NewHtlcEventMeta {
+                address: transaction.sender.to_user_friendly_address(),
+                txid,
+                block_number: block_number as i32,
+                kind: kind.to_string(),
+                amount: (u64::from(transaction.value) + u64::from(transaction.fee)) as i64,
+                pre_image,
+            };
+            diesel::insert_into(htlc_events::table)
+                .values(&event_meta)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+        }
+        Ok(())
+    }
+
+    /// Records contract creations with their parties
+    fn push_contract_creation(&self, conn: &PgConnection, transaction: &Transaction, block_number: u32) -> Result<(), Error> {
+        let (contract_address, contract_type, parties) = match TransactionData::decode(transaction) {
+            Some(Ok(TransactionData::HtlcCreation { contract_address, sender, recipient, .. })) => {
+                (contract_address, "htlc", vec![(sender, ContractRole::HtlcSender), (recipient, ContractRole::HtlcRecipient)])
+            },
+            Some(Ok(TransactionData::VestingCreation { contract_address, owner, .. })) => {
+                (contract_address, "vesting", vec![(owner, ContractRole::Owner)])
+            },
+            _ => return Ok(()),
+        };
+        let contract_address = contract_address.to_user_friendly_address();
+
+        let contract_meta = NewContractMeta {
+            address: contract_address.clone(),
+            contract_type: contract_type.to_string(),
+            creation_txid: transaction.hash::<Blake2bHash>().to_hex(),
+            creation_block_number: block_number as i32,
+        };
+
+        let party_metas: Vec<NewContractPartyMeta> = Some((transaction.sender.clone(), ContractRole::Creator)).into_iter()
+            .chain(parties)
+            .map(|(party, role)| NewContractPartyMeta {
+                contract_address: contract_address.clone(),
+                party_address: party.to_user_friendly_address(),
+                role: role.as_str().to_string(),
+            })
+            .collect();
+
+        // Already recorded if the block is indexed again
+        let inserted = diesel::insert_into(contracts::table)
+            .values(&contract_meta)
+            .on_conflict(contracts::address)
+            .do_nothing()
+            .execute(conn)?;
+        if inserted > 0 {
+            diesel::insert_into(contract_parties::table)
+                .values(&party_metas)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+        }
+        Ok(())
+    }
+
+    /// Get the contracts an address is a party of, optionally only with a specific role
+    pub fn get_account_contracts(&self, party_address: &Address, role: Option<ContractRole>) -> Result<Vec<(ContractMeta, ContractPartyMeta)>, Error> {
+        use contract_parties::{dsl as dsl1};
+        use contracts::{dsl as dsl2};
+
+        let conn = self.db_conn()?;
+
+        let mut query = dsl1::contract_parties
+            .filter(dsl1::party_address.eq(party_address.to_user_friendly_address()))
+            .into_boxed();
+        if let Some(role) = role {
+            query = query.filter(dsl1::role.eq(role.as_str()));
+        }
+        let party_metas = query
+            .load::<ContractPartyMeta>(&conn)?;
+
+        let contract_addresses: Vec<&String> = party_metas.iter()
+            .map(|party_meta| &party_meta.contract_address)
+            .collect();
+        let contract_metas = dsl2::contracts
+            .filter(dsl2::address.eq_any(contract_addresses))
+            .order(dsl2::creation_block_number.desc())
+            .load::<ContractMeta>(&conn)?;
+
+        let mut contracts = Vec::new();
+        for contract_meta in contract_metas {
+            for party_meta in party_metas.iter().filter(|party_meta| party_meta.contract_address == contract_meta.address) {
+                contracts.push((contract_meta.clone(), party_meta.clone()));
+            }
+        }
+        Ok(contracts)
+    }
+
+    pub fn get_htlc(&self, address: &Address) -> Result<Option<HtlcMeta>, Error> {
+        use htlcs::dsl;
+        let htlc_meta = dsl::htlcs
+            .filter(dsl::address.eq(address.to_user_friendly_address()))
+            .first::<HtlcMeta>(&self.db_conn()?)
+            .optional()?;
+        Ok(htlc_meta)
+    }
+
+    /// Gets a page of HTLCs, newest first, optionally only those in a specific state at
+    /// `block_number`. The state is derived from the events the same way as in `HtlcInfo::new`.
+    pub fn get_htlcs(&self, state: Option<HtlcState>, block_number: u32, limit: i64, offset: i64) -> Result<Vec<HtlcMeta>, Error> {
+        use htlcs::dsl;
+
+        // redeemed or resolved early, and at least the total amount was taken out
+        let redeemed_condition = format!("EXISTS (SELECT 1 FROM htlc_events e WHERE e.address = htlcs.address AND e.kind IN ('{}', '{}')) \
+                                   AND (SELECT COALESCE(SUM(e.amount), 0) FROM htlc_events e WHERE e.address = htlcs.address AND e.kind <> '{}') >= htlcs.total_amount",
+                                  HTLC_EVENT_REGULAR_TRANSFER, HTLC_EVENT_EARLY_RESOLVE, HTLC_EVENT_FUNDING);
+        let is_redeemed = || sql::<Bool>(&redeemed_condition);
+        let block_number = block_number as i32;
+
+        let mut query = dsl::htlcs.into_boxed();
+        match state {
+            None => {},
+            Some(HtlcState::Redeemed) => query = query.filter(is_redeemed()),
+            Some(HtlcState::TimedOut) => query = query.filter(not(is_redeemed()).and(dsl::timeout.le(block_number))),
+            Some(HtlcState::Pending) => query = query.filter(not(is_redeemed()).and(dsl::timeout.gt(block_number))),
+        }
+
+        let htlc_metas = query
+            .order((dsl::creation_block_number.desc(), dsl::id.desc()))
+            .limit(limit)
+            .offset(offset)
+            .load::<HtlcMeta>(&self.db_conn()?)?;
+        Ok(htlc_metas)
+    }
+
+    pub fn get_htlc_events(&self, address: &Address) -> Result<Vec<HtlcEventMeta>, Error> {
+        self.get_htlc_events_for(&[address.to_user_friendly_address()])
+    }
+
+    /// Gets the events of all of the HTLCs in one query
+    pub fn get_htlc_events_for(&self, addresses: &[String]) -> Result<Vec<HtlcEventMeta>, Error> {
+        use htlc_events::dsl;
+        let event_metas = dsl::htlc_events
+            .filter(dsl::address.eq_any(addresses))
+            .order(dsl::id.asc())
+            .load::<HtlcEventMeta>(&self.db_conn()?)?;
+        Ok(event_metas)
+    }
+
+    /// Set address of the staking contract, which is needed to index stakes
+    pub fn set_staking_contract(&mut self, address: Address) {
+        self.staking_contract = Some(address);
+    }
+
+    pub fn get_staking_contract(&self) -> Option<&Address> {
+        self.staking_contract.as_ref()
+    }
+
+    /// Get stakers that pay their rewards to this address
+    pub fn get_stakes_by_reward_address(&self, reward_address: &Address) -> Result<Vec<StakeMeta>, Error> {
+        use stakes::dsl;
+        let stake_metas = dsl::stakes
+            .filter(dsl::reward_address.eq(reward_address.to_user_friendly_address()))
+            .load::<StakeMeta>(&self.db_conn()?)?;
+        Ok(stake_metas)
+    }
+
+    /// Get stakers that stake for this validator
+    pub fn get_stakes_by_validator_key(&self, validator_key: &CompressedPublicKey) -> Result<Vec<StakeMeta>, Error> {
+        use stakes::dsl;
+        let stake_metas = dsl::stakes
+            .filter(dsl::validator_key.eq(validator_key.to_hex()))
+            .load::<StakeMeta>(&self.db_conn()?)?;
+        Ok(stake_metas)
     }
 
     fn push_fork_proof(&self, _fork_proof: &ForkProof, _block_hash: &Blake2bHash, _fork_proof_idx: usize) {
@@ -133,14 +764,14 @@ impl MetadataStore {
     }
 
     /// Get secondary aliases from database - not used at the moment.
-    pub fn get_account_aliases(&self, address: &Address) -> Vec<String> {
+    pub fn get_account_aliases(&self, address: &Address) -> Result<Vec<String>, Error> {
         use account_aliases::dsl;
-        dsl::account_aliases.filter(dsl::address.eq(address.to_user_friendly_address()))
-            .load::<AccountAlias>(&self.db_conn())
-            .expect("Failed to fetch account aliases from database")
+        let aliases = dsl::account_aliases.filter(dsl::address.eq(address.to_user_friendly_address()))
+            .load::<AccountAlias>(&self.db_conn()?)?
             .into_iter()
             .map(|alias| alias.alias)
-            .collect()
+            .collect();
+        Ok(aliases)
     }
 
     /// Set in-memory primary alias
@@ -153,55 +784,51 @@ impl MetadataStore {
         self.account_aliases.get(address).cloned()
     }
 
-    pub fn get_known_account_addresses(&self) -> Vec<Address> {
+    pub fn get_known_account_addresses(&self) -> Result<Vec<Address>, Error> {
         use account_aliases::{dsl as dsl1};
         use transactions::{dsl as dsl2};
 
-        let conn = self.db_conn();
+        let conn = self.db_conn()?;
         let mut addresses = HashSet::new();
 
         // get all addresses with aliases
         // TODO: We shouldn't use this, I think?
         for alias in dsl1::account_aliases
-            .load::<AccountAlias>(&conn)
-            .expect("Failed to fetch account aliases from database") {
+            .load::<AccountAlias>(&conn)? {
             addresses.insert(alias.address);
         }
 
         // get all other accounts we saw transactions for
         for tx_meta in dsl2::transactions
-            .load::<TransactionMeta>(&conn)
-            .expect("Failed to fetch transaction meta data from database") {
+            .load::<TransactionMeta>(&conn)? {
             addresses.insert(tx_meta.sender);
             addresses.insert(tx_meta.recipient);
         }
 
-        addresses.into_iter()
-            .map(|address| {
-                Address::from_user_friendly_address(&address)
-                    .unwrap_or_else(|e| panic!("Failed to parse Address from database: {}", e))
-            })
+        addresses.iter()
+            .map(|address| parse_address(address))
             .collect()
     }
 
-    pub fn get_account_transactions(&self, address: &Address) -> Vec<TransactionMeta> {
+    pub fn get_account_transactions(&self, address: &Address) -> Result<Vec<TransactionMeta>, Error> {
         use transactions::dsl;
         let address = address.to_user_friendly_address();
 
-        dsl::transactions
+        let tx_metas = dsl::transactions
             .filter(dsl::recipient.eq(&address).or(dsl::sender.eq(&address)))
             .order((dsl::block_number.desc(), dsl::tx_idx.desc()))
-            .load::<TransactionMeta>(&self.db_conn())
-            .expect("Failed to fetch account transactions from database")
+            .load::<TransactionMeta>(&self.db_conn()?)?;
+        Ok(tx_metas)
     }
 
-    pub fn get_transaction(&self, txid: &Blake2bHash) -> Option<TransactionMeta> {
+    pub fn get_transaction(&self, txid: &Blake2bHash) -> Result<Option<TransactionMeta>, Error> {
         use transactions::dsl;
         let txid = txid.to_hex();
 
-        dsl::transactions
+        let tx_meta = dsl::transactions
             .filter(dsl::txid.eq(&txid))
-            .first::<TransactionMeta>(&self.db_conn())
-            .ok()
+            .first::<TransactionMeta>(&self.db_conn()?)
+            .optional()?;
+        Ok(tx_meta)
     }
 }
@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use rocket::{Request, Response, Data, State};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::ContentType;
+use rocket::response::Content;
+
+use nimiq_primitives::policy::epoch_at;
+
+use crate::albatross::Albatross;
+
+
+#[derive(Clone, Debug, Default)]
+struct RouteStats {
+    count: u64,
+    latency_secs: f64,
+}
+
+/// Collects request counts and latencies per route. Attached as a fairing and also managed, so
+/// that the `/metrics` handler can read the counters.
+#[derive(Clone, Debug, Default)]
+pub struct RequestMetrics {
+    /// Keyed by method, route and status code
+    routes: Arc<Mutex<BTreeMap<(String, String, u16), RouteStats>>>,
+}
+
+/// Start of the request, stored in the request-local cache
+struct RequestStart(Option<Instant>);
+
+impl Fairing for RequestMetrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request metrics",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    fn on_request(&self, request: &mut Request, _data: &Data) {
+        request.local_cache(|| RequestStart(Some(Instant::now())));
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        let start = request.local_cache(|| RequestStart(None));
+        let latency_secs = match start.0 {
+            Some(start) => {
+                let elapsed = start.elapsed();
+                elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9
+            },
+            None => 0.0,
+        };
+
+        // Use the route's URI, so that identifiers don't each get their own label
+        let route = request.route()
+            .map(|route| route.uri.path().to_string())
+            .unwrap_or_else(|| "unmatched".to_string());
+
+        let key = (request.method().as_str().to_string(), route, response.status().code);
+        let mut routes = self.routes.lock().expect("Request metrics lock poisoned");
+        let stats = routes.entry(key).or_default();
+        stats.count += 1;
+        stats.latency_secs += latency_secs;
+    }
+}
+
+/// Writes metrics in the Prometheus text format
+#[derive(Default)]
+struct MetricsWriter {
+    out: String,
+}
+
+impl MetricsWriter {
+    fn header(&mut self, name: &str, kind: &str, help: &str) {
+        writeln!(self.out, "# HELP {} {}", name, help).unwrap();
+        writeln!(self.out, "# TYPE {} {}", name, kind).unwrap();
+    }
+
+    fn gauge<V: Display>(&mut self, name: &str, help: &str, value: V) {
+        self.header(name, "gauge", help);
+        writeln!(self.out, "{} {}", name, value).unwrap();
+    }
+
+    fn counter<V: Display>(&mut self, name: &str, help: &str, value: V) {
+        self.header(name, "counter", help);
+        writeln!(self.out, "{} {}", name, value).unwrap();
+    }
+
+    fn sample<V: Display>(&mut self, name: &str, labels: &[(&str, &str)], value: V) {
+        let labels = labels.iter()
+            .map(|(key, value)| format!("{}=\"{}\"", key, value.replace('\\', "\\\\").replace('"', "\\\"")))
+            .collect::<Vec<String>>()
+            .join(",");
+        writeln!(self.out, "{}{{{}}} {}", name, labels, value).unwrap();
+    }
+}
+
+#[get("/metrics")]
+pub fn get_metrics(albatross: State<Albatross>, request_metrics: State<RequestMetrics>) -> Content<String> {
+    let mut w = MetricsWriter::default();
+
+    let block_number = albatross.block_number();
+    w.gauge("explorer_head_block_number", "Block number of the chain head", block_number);
+    w.gauge("explorer_head_epoch", "Epoch of the chain head", epoch_at(block_number));
+    w.gauge("explorer_consensus_established", "Whether consensus is established", albatross.is_consensus_established() as u8);
+    w.gauge("explorer_peers", "Number of connected peers", albatross.num_peers());
+    w.gauge("explorer_mempool_transactions", "Number of transactions in the mempool", albatross.mempool_size());
+
+    let health = albatross.get_health_info().ok();
+    let database_reachable = health.as_ref().map(|health| health.database_reachable).unwrap_or(false);
+    w.gauge("explorer_database_reachable", "Whether the database is reachable", database_reachable as u8);
+    if database_reachable {
+        let meta_store = albatross.meta_store();
+        if let Ok(num_blocks) = meta_store.count_indexed_blocks() {
+            w.counter("explorer_indexed_blocks_total", "Number of indexed blocks", num_blocks);
+        }
+        if let Ok(num_transactions) = meta_store.count_indexed_transactions() {
+            w.counter("explorer_indexed_transactions_total", "Number of indexed transactions", num_transactions);
+        }
+
+        let (connections, idle_connections, max_size) = meta_store.pool_state();
+        w.gauge("explorer_db_pool_connections", "Open database connections", connections);
+        w.gauge("explorer_db_pool_idle_connections", "Idle database connections", idle_connections);
+        w.gauge("explorer_db_pool_max_size", "Maximum number of database connections", max_size);
+    }
+    if let Some(indexer_lag) = health.and_then(|health| health.indexer_lag) {
+        w.gauge("explorer_indexer_lag_blocks", "Number of blocks the indexer is behind the head", indexer_lag);
+    }
+
+    let routes = request_metrics.routes.lock().expect("Request metrics lock poisoned").clone();
+    w.header("explorer_http_requests_total", "counter", "Number of HTTP requests");
+    for ((method, route, status), stats) in &routes {
+        let status = status.to_string();
+        let labels = [("method", method.as_str()), ("route", route.as_str()), ("status", status.as_str())];
+        w.sample("explorer_http_requests_total", &labels, stats.count);
+    }
+    w.header("explorer_http_request_duration_seconds", "summary", "Time spent handling HTTP requests");
+    for ((method, route, status), stats) in &routes {
+        let status = status.to_string();
+        let labels = [("method", method.as_str()), ("route", route.as_str()), ("status", status.as_str())];
+        w.sample("explorer_http_request_duration_seconds_sum", &labels, stats.latency_secs);
+        w.sample("explorer_http_request_duration_seconds_count", &labels, stats.count);
+    }
+
+    Content(ContentType::new("text", "plain").with_params(("version", "0.0.4")), w.out)
+}
@@ -4,9 +4,20 @@ pub mod error;
 pub mod dashboard;
 pub mod blockchain;
 pub mod transaction;
+pub mod transaction_data;
+pub mod transaction_proof;
 pub mod search;
 pub mod renderer;
 pub mod metadata;
 pub mod account;
+pub mod verification;
+pub mod rewards;
+pub mod htlc;
+pub mod contract;
+pub mod decoder;
+pub mod broadcast;
+pub mod network;
+pub mod health;
+pub mod metrics;
 
-pub use renderer::{ResourceRenderer, Download};
+pub use renderer::{ResourceRenderer, Download, DownloadFormat, StreamDownload};
@@ -0,0 +1,83 @@
+use std::time::Instant;
+
+use serde::Serialize;
+use rocket::State;
+use rocket_contrib::templates::Template;
+use rocket_contrib::json::Json;
+
+use nimiq_hash::Blake2bHash;
+
+use crate::albatross::Albatross;
+use crate::error::Error;
+use crate::resource::ResourceRenderer;
+use crate::resource::block::BlockInfo;
+use crate::utils::{serialize_with_format_opt, short_hash};
+
+
+/// A peer we're connected to
+#[derive(Clone, Debug, Serialize)]
+pub struct PeerInfo {
+    address: String,
+    services: String,
+    protocol: String,
+    version: Option<u32>,
+
+    /// Seconds since the connection was established
+    connected_secs: Option<u64>,
+
+    /// Head of the peer, as it announced it. The block number is only known if we have the block.
+    #[serde(serialize_with = "serialize_with_format_opt")]
+    head_hash: Option<Blake2bHash>,
+    short_head_hash: Option<String>,
+    head_block_number: Option<u32>,
+}
+
+impl PeerInfo {
+    pub fn new(address: String, services: String, protocol: String, version: Option<u32>, established_since: Option<Instant>, head_hash: Option<Blake2bHash>, head_block_number: Option<u32>) -> Self {
+        Self {
+            address,
+            services,
+            protocol,
+            version,
+            connected_secs: established_since.map(|since| since.elapsed().as_secs()),
+            short_head_hash: head_hash.as_ref().map(short_hash),
+            head_hash,
+            head_block_number,
+        }
+    }
+}
+
+/// Status of our node in the network
+#[derive(Clone, Debug, Serialize)]
+pub struct NetworkStatusInfo {
+    network_name: String,
+    is_validator: bool,
+    consensus_established: bool,
+    num_peers: usize,
+    peers: Vec<PeerInfo>,
+    head: BlockInfo,
+}
+
+impl NetworkStatusInfo {
+    pub fn new(network_name: String, is_validator: bool, consensus_established: bool, peers: Vec<PeerInfo>, head: BlockInfo) -> Self {
+        Self {
+            network_name,
+            is_validator,
+            consensus_established,
+            num_peers: peers.len(),
+            peers,
+            head,
+        }
+    }
+}
+
+#[get("/network-info")]
+pub fn get_network_info(albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, Error> {
+    let network_info = albatross.get_network_status_info()?;
+    Ok(renderer.render("network", network_info, &albatross))
+}
+
+#[get("/network-info")]
+pub fn api_get_network_info(albatross: State<Albatross>) -> Result<Json<NetworkStatusInfo>, Error> {
+    albatross.get_network_status_info().map(Json)
+}
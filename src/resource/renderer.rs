@@ -1,18 +1,119 @@
 use std::borrow::Cow;
 use std::fmt::Debug;
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 
 use serde::Serialize;
 use rocket_contrib::templates::Template;
-use rocket::response::{Content, Stream};
-use rocket::http::ContentType;
+use rocket::request::{Request, FromFormValue};
+use rocket::response::{self, Responder, Response};
+use rocket::http::{ContentType, RawStr};
 
 use beserial::Serialize as BeSerialize;
 
 use crate::albatross::Albatross;
+use crate::config::FeaturesConfig;
+use crate::error::Error;
 
 
-pub type Download = Content<Stream<Cursor<Vec<u8>>>>;
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DownloadFormat {
+    Beserial,
+    Hex,
+    Json,
+}
+
+impl DownloadFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            DownloadFormat::Beserial => "bin",
+            DownloadFormat::Hex => "hex",
+            DownloadFormat::Json => "json",
+        }
+    }
+}
+
+impl Default for DownloadFormat {
+    fn default() -> Self {
+        DownloadFormat::Beserial
+    }
+}
+
+impl<'v> FromFormValue<'v> for DownloadFormat {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<Self, Self::Error> {
+        match form_value.as_str() {
+            "beserial" => Ok(DownloadFormat::Beserial),
+            "hex" => Ok(DownloadFormat::Hex),
+            "json" => Ok(DownloadFormat::Json),
+            _ => Err(form_value),
+        }
+    }
+}
+
+#[derive(Debug, Fail)]
+pub enum DownloadError {
+    #[fail(display = "Serialization failed: {}", _0)]
+    Serializing(beserial::SerializingError),
+    #[fail(display = "JSON serialization failed: {}", _0)]
+    Json(serde_json::Error),
+}
+
+impl From<beserial::SerializingError> for DownloadError {
+    fn from(e: beserial::SerializingError) -> Self {
+        DownloadError::Serializing(e)
+    }
+}
+
+impl From<serde_json::Error> for DownloadError {
+    fn from(e: serde_json::Error) -> Self {
+        DownloadError::Json(e)
+    }
+}
+
+/// A file download, with a filename for the `Content-Disposition` header
+#[derive(Debug)]
+pub struct Download {
+    content_type: ContentType,
+    filename: String,
+    data: Vec<u8>,
+}
+
+impl<'r> Responder<'r> for Download {
+    fn respond_to(self, _request: &Request) -> response::Result<'r> {
+        Response::build()
+            .header(self.content_type)
+            .raw_header("Content-Disposition", format!("attachment; filename=\"{}\"", self.filename))
+            .sized_body(Cursor::new(self.data))
+            .ok()
+    }
+}
+
+/// A binary download that is streamed instead of being serialized into memory first
+#[derive(Debug)]
+pub struct StreamDownload<R: Read> {
+    filename: String,
+    reader: R,
+}
+
+impl<R: Read> StreamDownload<R> {
+    pub fn new(filename: String, reader: R) -> Self {
+        Self {
+            filename,
+            reader,
+        }
+    }
+}
+
+impl<'r, R: Read + 'r> Responder<'r> for StreamDownload<R> {
+    fn respond_to(self, _request: &Request) -> response::Result<'r> {
+        Response::build()
+            .header(ContentType::Binary)
+            .raw_header("Content-Disposition", format!("attachment; filename=\"{}\"", self.filename))
+            .streamed_body(self.reader)
+            .ok()
+    }
+}
 
 #[derive(Clone, Debug, Serialize)]
 pub struct ResourceContext<C: Serialize> {
@@ -21,6 +122,9 @@ pub struct ResourceContext<C: Serialize> {
     search_placeholder: String,
     network_name: String,
     block_number: u32,
+    health_warning: Option<String>,
+    features: FeaturesConfig,
+    is_validator: bool,
     debug_content: Option<String>,
 }
 
@@ -28,6 +132,7 @@ pub struct ResourceContext<C: Serialize> {
 pub struct ResourceRenderer {
     base_template: String,
     send_debug_content: bool,
+    features: FeaturesConfig,
 }
 
 impl ResourceRenderer {
@@ -35,9 +140,16 @@ impl ResourceRenderer {
         Self {
             base_template: base_template.as_ref().to_string(),
             send_debug_content: false,
+            features: FeaturesConfig::default(),
         }
     }
 
+    /// Sets the enabled features, so that pages only link to those
+    pub fn with_features(mut self, features: FeaturesConfig) -> Self {
+        self.features = features;
+        self
+    }
+
     pub fn render<S: Into<Cow<'static, str>>, C: Serialize + Debug>(&self, name: S, content: C, albatross: &Albatross) -> Template {
         // TODO: If we do the rendering as part of a Guard, the handlers only have to fetch the data and
         // return a `Render<S: Into<Cow<'static, str>>, C: Serialize> { page: S, content: C, albatross: &'a Albatross }`
@@ -57,14 +169,43 @@ impl ResourceRenderer {
             base: self.base_template.clone(),
             network_name: format!("{}", albatross.network_id()),
             block_number: albatross.block_number(),
+            health_warning: albatross.get_health_info().ok().and_then(|health| health.warning()),
+            features: self.features.clone(),
+            is_validator: albatross.is_validator(),
             debug_content,
         })
     }
 
-    pub fn download<C: BeSerialize>(&self, content: C) -> Result<Download, beserial::SerializingError> {
-        let mut buf = Vec::with_capacity(BeSerialize::serialized_size(&content));
-        BeSerialize::serialize(&content, &mut buf)?;
-        Ok(Content(ContentType::Binary, Stream::from(Cursor::new(buf))))
+    /// Serializes `content` for download. For the JSON format the result of `info` is serialized
+    /// instead, which should be the same view of the content that is used for the page. It's only
+    /// called for JSON. The file extension is appended to `name`.
+    pub fn download<C, I, F>(&self, content: &C, info: F, name: &str, format: DownloadFormat) -> Result<Download, Error>
+        where C: BeSerialize, I: Serialize, F: FnOnce() -> Result<I, Error>
+    {
+        let filename = format!("{}.{}", name, format.extension());
+
+        let data = match format {
+            DownloadFormat::Json => serde_json::to_vec_pretty(&info()?)
+                .map_err(DownloadError::from),
+            DownloadFormat::Beserial | DownloadFormat::Hex => {
+                let mut buf = Vec::with_capacity(BeSerialize::serialized_size(content));
+                BeSerialize::serialize(content, &mut buf)
+                    .map(|_| buf)
+                    .map_err(DownloadError::from)
+            },
+        }.map_err(|e| Error::Serializing(e.to_string()))?;
+
+        let (content_type, data) = match format {
+            DownloadFormat::Beserial => (ContentType::Binary, data),
+            DownloadFormat::Hex => (ContentType::Plain, hex::encode(&data).into_bytes()),
+            DownloadFormat::Json => (ContentType::JSON, data),
+        };
+
+        Ok(Download {
+            content_type,
+            filename,
+            data,
+        })
     }
 }
 
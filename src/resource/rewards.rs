@@ -0,0 +1,128 @@
+use serde::Serialize;
+
+use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
+use nimiq_primitives::coin::Coin;
+use nimiq_collections::bitset::BitSet;
+
+use crate::utils::{serialize_with_format, serialize_address, short_hash};
+
+
+/// Rewards paid out to one slot owner at the end of an epoch
+#[derive(Clone, Debug, Serialize)]
+pub struct EpochRewardInfo {
+    pub epoch: u32,
+
+    #[serde(serialize_with = "serialize_with_format")]
+    pub block_hash: Blake2bHash,
+    short_block_hash: String,
+
+    #[serde(serialize_with = "serialize_address")]
+    pub staker_address: Address,
+
+    #[serde(serialize_with = "serialize_address")]
+    pub reward_address: Address,
+
+    /// Number of slots this staker had in the epoch
+    pub num_slots: u16,
+
+    /// Number of those slots that were slashed
+    pub num_slashed: u16,
+
+    /// Reward before slashing
+    #[serde(serialize_with = "serialize_with_format")]
+    pub reward: Coin,
+
+    /// Reward of the slashed slots, which is burned
+    #[serde(serialize_with = "serialize_with_format")]
+    pub slash_deduction: Coin,
+
+    /// Amount actually paid to the reward address
+    #[serde(serialize_with = "serialize_with_format")]
+    pub payout: Coin,
+}
+
+impl EpochRewardInfo {
+    pub fn new(epoch: u32, block_hash: Blake2bHash, staker_address: Address, reward_address: Address, num_slots: u16, num_slashed: u16, reward: Coin, slash_deduction: Coin) -> Self {
+        Self {
+            epoch,
+            short_block_hash: short_hash(&block_hash),
+            block_hash,
+            staker_address,
+            reward_address,
+            num_slots,
+            num_slashed,
+            reward,
+            slash_deduction,
+            payout: Coin::from_u64_unchecked(u64::from(reward).saturating_sub(u64::from(slash_deduction))),
+        }
+    }
+
+    /// Computes the rewards of an epoch the way the chain distributes them in
+    /// `Blockchain::finalize_last_epoch` (nimiq-blockchain-albatross).
+    ///
+    /// `slot_owners` are the `(staker_address, reward_address)` of each slot of the epoch. Every
+    /// slot is worth `reward_pot / num_slots`. The reward of slashed slots is burned instead of
+    /// paid out. The remainder of the division (less than one Luna per slot) is given to one
+    /// validator that is picked randomly with the macro block's seed, so it isn't attributed here.
+    pub fn compute(epoch: u32, block_hash: &Blake2bHash, slot_owners: &[(Address, Address)], slashed_set: &BitSet, reward_pot: Coin) -> Vec<Self> {
+        let num_slots = slot_owners.len() as u64;
+        if num_slots == 0 {
+            return vec![];
+        }
+        let slot_reward = u64::from(reward_pot) / num_slots;
+
+        // group slots by owner, keeping the order of the slots
+        let mut owners: Vec<(&(Address, Address), u16, u16)> = Vec::new();
+        for (slot, owner) in slot_owners.iter().enumerate() {
+            let idx = match owners.iter().position(|entry| entry.0 == owner) {
+                Some(idx) => idx,
+                None => {
+                    owners.push((owner, 0, 0));
+                    owners.len() - 1
+                },
+            };
+
+            let entry = &mut owners[idx];
+            entry.1 += 1;
+            if slashed_set.contains(slot) {
+                entry.2 += 1;
+            }
+        }
+
+        owners.into_iter()
+            .map(|((staker_address, reward_address), num_slots, num_slashed)| {
+                Self::new(epoch, block_hash.clone(), staker_address.clone(), reward_address.clone(), num_slots, num_slashed,
+                          Coin::from_u64_unchecked(slot_reward * u64::from(num_slots)),
+                          Coin::from_u64_unchecked(slot_reward * u64::from(num_slashed)))
+            })
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slashed_slots_are_burned() {
+        let alice = (Address::from([1u8; 20]), Address::from([2u8; 20]));
+        let bob = (Address::from([3u8; 20]), Address::from([4u8; 20]));
+        let slot_owners = vec![alice.clone(), alice.clone(), alice, bob.clone(), bob];
+        let mut slashed_set = BitSet::new();
+        slashed_set.insert(1);
+        slashed_set.insert(4);
+        let reward_pot = 1003;
+
+        let rewards = EpochRewardInfo::compute(1, &Blake2bHash::default(), &slot_owners, &slashed_set, Coin::from_u64_unchecked(reward_pot));
+        assert_eq!(rewards.len(), 2);
+        assert_eq!((rewards[0].num_slots, rewards[0].num_slashed, u64::from(rewards[0].payout)), (3, 1, 400));
+        assert_eq!((rewards[1].num_slots, rewards[1].num_slashed, u64::from(rewards[1].payout)), (2, 1, 200));
+
+        // payouts, burned rewards and the remainder add up to the pot
+        let paid: u64 = rewards.iter().map(|reward| u64::from(reward.payout)).sum();
+        let burned: u64 = rewards.iter().map(|reward| u64::from(reward.slash_deduction)).sum();
+        assert_eq!(paid + burned + reward_pot % slot_owners.len() as u64, reward_pot);
+    }
+}
@@ -1,9 +1,15 @@
+use serde::Serialize;
 use rocket::State;
 use rocket::request::Form;
 use rocket_contrib::templates::Template;
 
 use crate::resource::ResourceRenderer;
+use crate::resource::transaction::TransactionInfo;
 use crate::albatross::{Albatross, AnyIdentifier, ParseError};
+use crate::error::Error;
+
+
+const MAX_SEARCH_RESULTS: usize = 50;
 
 
 #[derive(FromForm)]
@@ -11,19 +17,30 @@ pub struct SearchParams {
     q: String,
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchResultsInfo {
+    query: String,
+    /// Transactions with messages matching the query
+    transactions: Vec<TransactionInfo>,
+}
+
 #[get("/search?<params..>")]
-pub fn get_search(params: Form<SearchParams>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, ()> {
+pub fn get_search(params: Form<SearchParams>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, Error> {
     match params.q.parse::<AnyIdentifier>() {
         Ok(_ident) => {
             // TODO redirect to the appropriate page
-            Err(())
+            Err(Error::NotFound("Search result", params.q.clone()))
         },
-        Err(ParseError::Unrecognized(_q)) => {
+        Err(ParseError::Unrecognized(q)) => {
             // TODO Search other stuff for s (e.g. aliases)
-            Ok(renderer.render("search-results", (), &albatross))
+            let transactions = albatross.search_messages(&q, MAX_SEARCH_RESULTS)?;
+            Ok(renderer.render("search-results", SearchResultsInfo {
+                query: q,
+                transactions,
+            }, &albatross))
         },
-        Err(_) => {
-            Err(())
+        Err(e) => {
+            Err(e.into())
         },
     }
 }
@@ -1,6 +1,8 @@
 use serde::{Serialize, Serializer};
 use rocket_contrib::templates::Template;
 use rocket::State;
+use rocket::request::Form;
+use rocket_contrib::json::Json;
 
 use nimiq_transaction::{Transaction, TransactionFlags};
 use nimiq_keys::Address;
@@ -9,19 +11,18 @@ use nimiq_primitives::coin::Coin;
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_primitives::policy::epoch_at;
 
-use crate::resource::{ResourceRenderer, Download};
-use crate::albatross::{Albatross, TransactionIdentifier};
+use crate::resource::{ResourceRenderer, Download, DownloadFormat};
+use crate::resource::transaction_data::TransactionData;
+use crate::resource::transaction_proof::TransactionProof;
+use crate::resource::verification::VerificationResult;
+use crate::albatross::{Albatross, TransactionIdentifier, ParseError};
+use crate::error::Error;
 use crate::utils::{serialize_with_format, serialize_with_hex, short_hash, serialize_address,
-                   serialize_with_format_opt, serialize_special_account_type};
+                   serialize_with_format_opt, serialize_special_account_type, printable_utf8,
+                   hex_dump, HexDumpLine};
 
 
 #[derive(Clone, Debug, Serialize)]
-pub struct TransactionData {
-
-}
-
-#[derive(Clone, Debug, Serialize)]
-// TODO: interpret transaction data
 pub struct TransactionInfo {
     /// Short transaction ID
     short_txid: String,
@@ -78,9 +79,19 @@ pub struct TransactionInfo {
     #[serde(serialize_with = "serialize_with_hex")]
     data_raw: Vec<u8>,
     data: Option<TransactionData>,
+    /// Why the data couldn't be interpreted
+    data_error: Option<String>,
+    /// Data as text, if it's printable UTF-8 and not interpreted otherwise
+    message: Option<String>,
+    data_dump: Vec<HexDumpLine>,
 
     #[serde(serialize_with = "serialize_with_hex")]
-    proof: Vec<u8>,
+    proof_raw: Vec<u8>,
+    proof: Option<TransactionProof>,
+    /// Why the proof couldn't be decoded
+    proof_error: Option<String>,
+    /// Whether the proof's signatures are valid and match the sender
+    proof_verification: Option<VerificationResult>,
 }
 
 impl From<Transaction> for TransactionInfo {
@@ -92,6 +103,20 @@ impl From<Transaction> for TransactionInfo {
 impl TransactionInfo {
     pub fn new(transaction: Transaction, block_hash: Option<Blake2bHash>, block_tx_idx: Option<usize>, confirmation: Confirmation, is_in_mempool: bool) -> Self {
         let txid = transaction.hash();
+        let (data, data_error) = match TransactionData::decode(&transaction) {
+            Some(Ok(data)) => (Some(data), None),
+            Some(Err(e)) => (None, Some(e)),
+            None => (None, None),
+        };
+        let message = if data.is_none() { printable_utf8(&transaction.data) } else { None };
+        let data_dump = hex_dump(&transaction.data);
+        let (proof, proof_error, proof_verification) = match TransactionProof::decode(&transaction) {
+            Ok(proof) => {
+                let verification = proof.verify(&transaction);
+                (Some(proof), None, Some(verification))
+            },
+            Err(e) => (None, Some(format!("Invalid proof: {}", e)), None),
+        };
 
         Self {
             short_txid: short_hash(&txid),
@@ -112,8 +137,14 @@ impl TransactionInfo {
             fee: transaction.fee,
             validity_start_height: transaction.validity_start_height,
             data_raw: transaction.data,
-            data: None,
-            proof: transaction.proof,
+            data,
+            data_error,
+            message,
+            data_dump,
+            proof_raw: transaction.proof,
+            proof,
+            proof_error,
+            proof_verification,
         }
     }
 }
@@ -154,15 +185,27 @@ impl Default for Confirmation {
 
 
 #[get("/transaction/<ident>")]
-pub fn get_transaction(ident: TransactionIdentifier, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Option<Template> {
+pub fn get_transaction(ident: Result<TransactionIdentifier, ParseError>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Template, Error> {
+    let ident = ident?;
     let block_info = albatross.get_transaction_info(&ident)?;
-    Some(renderer.render("transaction", block_info, &albatross))
+    Ok(renderer.render("transaction", block_info, &albatross))
+}
+
+#[get("/transaction/<ident>")]
+pub fn api_get_transaction(ident: Result<TransactionIdentifier, ParseError>, albatross: State<Albatross>) -> Result<Json<TransactionInfo>, Error> {
+    let ident = ident?;
+    albatross.get_transaction_info(&ident).map(Json)
+}
+
+#[derive(FromForm)]
+pub struct DownloadParams {
+    format: Option<DownloadFormat>,
 }
 
-#[get("/transaction/<ident>/download")]
-pub fn download_transaction(ident: TransactionIdentifier, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Download, ()> {
-    //let transaction = albatross.
-    //renderer.download(chain_info.head).map_err(|e| warn!("Download failed: {}", e))
-    let _ = (ident, albatross, renderer); // those variables are not unused ;)
-    unimplemented!();
+#[get("/transaction/<ident>/download?<params..>")]
+pub fn download_transaction(ident: Result<TransactionIdentifier, ParseError>, params: Form<DownloadParams>, albatross: State<Albatross>, renderer: State<ResourceRenderer>) -> Result<Download, Error> {
+    let ident = ident?;
+    let transaction = albatross.get_transaction(&ident)?;
+    let name = format!("transaction-{}", ident);
+    renderer.download(&transaction, || albatross.get_transaction_info(&ident), &name, params.format.unwrap_or_default())
 }
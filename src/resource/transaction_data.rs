@@ -0,0 +1,177 @@
+use serde::Serialize;
+
+use nimiq_transaction::{Transaction, TransactionFlags, SignatureProof};
+use nimiq_transaction::account::staking_contract::StakingTransactionData;
+use nimiq_transaction::account::htlc_contract::{AnyHash, HashAlgorithm, CreationTransactionData as HtlcCreationData};
+use nimiq_transaction::account::vesting_contract::CreationTransactionData as VestingCreationData;
+use nimiq_primitives::account::AccountType;
+use nimiq_primitives::coin::Coin;
+use nimiq_keys::Address;
+use nimiq_bls::bls12_381::CompressedPublicKey as BlsPublicKey;
+use beserial::Deserialize;
+
+use crate::utils::{serialize_address, serialize_address_opt, serialize_with_beserial, serialize_with_format,
+                   short_validator_key};
+
+
+/// Type byte of a self transaction of the staking contract, i.e. a transaction from the staking
+/// contract to itself.
+const STAKING_SELF_TX_RETIRE: u8 = 0;
+const STAKING_SELF_TX_REACTIVATE: u8 = 1;
+
+
+/// Interpreted transaction data
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionData {
+    /// Stake in the staking contract
+    Stake {
+        #[serde(serialize_with = "serialize_with_beserial")]
+        validator_key: BlsPublicKey,
+        short_validator_key: String,
+        #[serde(serialize_with = "serialize_address_opt")]
+        reward_address: Option<Address>,
+    },
+
+    /// Retire active stake
+    Retire {
+        #[serde(serialize_with = "serialize_address_opt")]
+        staker_address: Option<Address>,
+    },
+
+    /// Re-activate inactive stake for a validator
+    Reactivate {
+        #[serde(serialize_with = "serialize_address_opt")]
+        staker_address: Option<Address>,
+        #[serde(serialize_with = "serialize_with_beserial")]
+        validator_key: BlsPublicKey,
+        short_validator_key: String,
+    },
+
+    /// Creation of a hashed time-locked contract
+    HtlcCreation {
+        #[serde(serialize_with = "serialize_address")]
+        contract_address: Address,
+        #[serde(serialize_with = "serialize_address")]
+        sender: Address,
+        #[serde(serialize_with = "serialize_address")]
+        recipient: Address,
+        #[serde(serialize_with = "serialize_with_format")]
+        hash_algorithm: HashAlgorithm,
+        #[serde(serialize_with = "serialize_with_format")]
+        hash_root: AnyHash,
+        hash_count: u8,
+        timeout: u32,
+    },
+
+    /// Creation of a vesting contract
+    VestingCreation {
+        #[serde(serialize_with = "serialize_address")]
+        contract_address: Address,
+        #[serde(serialize_with = "serialize_address")]
+        owner: Address,
+        start: u32,
+        step_blocks: u32,
+        #[serde(serialize_with = "serialize_with_format")]
+        step_amount: Coin,
+        #[serde(serialize_with = "serialize_with_format")]
+        total_amount: Coin,
+    },
+
+    /// Unpark and withdraw inactive stake from the staking contract
+    Unstake {
+        #[serde(serialize_with = "serialize_address_opt")]
+        staker_address: Option<Address>,
+        #[serde(serialize_with = "serialize_address")]
+        recipient: Address,
+    },
+}
+
+impl TransactionData {
+    /// Decodes the data of a transaction. Returns `None` if the transaction doesn't carry data we
+    /// know how to interpret, or an error message if the data is malformed.
+    pub fn decode(transaction: &Transaction) -> Option<Result<Self, String>> {
+        if transaction.flags.contains(TransactionFlags::CONTRACT_CREATION) {
+            return Self::decode_contract_creation(transaction);
+        }
+
+        match (transaction.sender_type, transaction.recipient_type) {
+            (AccountType::Staking, AccountType::Staking) => Some(Self::decode_staking_self(transaction)),
+            (_, AccountType::Staking) => Some(Self::decode_stake(transaction)),
+            (AccountType::Staking, _) => Some(Ok(TransactionData::Unstake {
+                staker_address: signer_address(transaction),
+                recipient: transaction.recipient.clone(),
+            })),
+            _ => None,
+        }
+    }
+
+    fn decode_contract_creation(transaction: &Transaction) -> Option<Result<Self, String>> {
+        let contract_address = transaction.contract_creation_address();
+        match transaction.recipient_type {
+            AccountType::HTLC => Some(HtlcCreationData::parse(transaction)
+                .map(|data| TransactionData::HtlcCreation {
+                    contract_address,
+                    sender: data.sender,
+                    recipient: data.recipient,
+                    hash_algorithm: data.hash_algorithm,
+                    hash_root: data.hash_root,
+                    hash_count: data.hash_count,
+                    timeout: data.timeout,
+                })
+                .map_err(|e| format!("Invalid HTLC creation data: {}", e))),
+            AccountType::Vesting => Some(VestingCreationData::parse(transaction)
+                .map(|data| TransactionData::VestingCreation {
+                    contract_address,
+                    owner: data.owner,
+                    start: data.start,
+                    step_blocks: data.step_blocks,
+                    step_amount: data.step_amount,
+                    total_amount: data.total_amount,
+                })
+                .map_err(|e| format!("Invalid vesting contract creation data: {}", e))),
+            _ => None,
+        }
+    }
+
+    fn decode_stake(transaction: &Transaction) -> Result<Self, String> {
+        let data: StakingTransactionData = Deserialize::deserialize_from_vec(&transaction.data)
+            .map_err(|e| format!("Invalid staking data: {}", e))?;
+        Ok(TransactionData::Stake {
+            short_validator_key: short_validator_key(&data.validator_key),
+            validator_key: data.validator_key,
+            reward_address: data.reward_address,
+        })
+    }
+
+    /// Self transactions start with a type byte. Re-activating stake also specifies the new
+    /// validator key.
+    fn decode_staking_self(transaction: &Transaction) -> Result<Self, String> {
+        let mut reader = &transaction.data[..];
+        let tx_type: u8 = Deserialize::deserialize(&mut reader)
+            .map_err(|e| format!("Missing staking transaction type: {}", e))?;
+        let staker_address = signer_address(transaction);
+
+        match tx_type {
+            STAKING_SELF_TX_RETIRE => Ok(TransactionData::Retire {
+                staker_address,
+            }),
+            STAKING_SELF_TX_REACTIVATE => {
+                let validator_key: BlsPublicKey = Deserialize::deserialize(&mut reader)
+                    .map_err(|e| format!("Invalid validator key: {}", e))?;
+                Ok(TransactionData::Reactivate {
+                    staker_address,
+                    short_validator_key: short_validator_key(&validator_key),
+                    validator_key,
+                })
+            },
+            _ => Err(format!("Unknown staking transaction type: {}", tx_type)),
+        }
+    }
+}
+
+/// Address of the key that signed the transaction, if it has a signature proof
+fn signer_address(transaction: &Transaction) -> Option<Address> {
+    let proof: SignatureProof = Deserialize::deserialize_from_vec(&transaction.proof).ok()?;
+    Some(proof.compute_signer())
+}
@@ -0,0 +1,157 @@
+use serde::Serialize;
+
+use nimiq_transaction::{Transaction, SignatureProof};
+use nimiq_transaction::account::htlc_contract::{AnyHash, HashAlgorithm, ProofType};
+use nimiq_primitives::account::AccountType;
+use nimiq_keys::{Address, PublicKey, Signature};
+use nimiq_utils::merkle::Blake2bMerklePath;
+use beserial::{Deserialize, SerializingError};
+
+use crate::utils::{serialize_address, serialize_with_beserial, serialize_with_format};
+use crate::resource::verification::VerificationResult;
+
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SignatureProofInfo {
+    #[serde(serialize_with = "serialize_with_beserial")]
+    public_key: PublicKey,
+
+    /// Address derived from the public key and merkle path
+    #[serde(serialize_with = "serialize_address")]
+    signer_address: Address,
+
+    /// Merkle path of the public key in a multisig wallet. Empty for single-signature wallets.
+    #[serde(serialize_with = "serialize_with_beserial")]
+    merkle_path: Blake2bMerklePath,
+    is_multisig: bool,
+
+    #[serde(serialize_with = "serialize_with_beserial")]
+    signature: Signature,
+
+    /// Whether the signature is valid for the transaction
+    #[serde(skip)]
+    is_valid: bool,
+}
+
+impl SignatureProofInfo {
+    fn new(proof: SignatureProof, message: &[u8]) -> Self {
+        Self {
+            signer_address: proof.compute_signer(),
+            is_multisig: proof.merkle_path.len() > 0,
+            is_valid: proof.verify(message),
+            public_key: proof.public_key,
+            merkle_path: proof.merkle_path,
+            signature: proof.signature,
+        }
+    }
+}
+
+/// Interpreted transaction proof
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionProof {
+    /// Signature of the sender
+    Signature(SignatureProofInfo),
+
+    /// HTLC redeemed by the recipient, revealing the pre-image
+    HtlcRegularTransfer {
+        #[serde(serialize_with = "serialize_with_format")]
+        hash_algorithm: HashAlgorithm,
+        hash_depth: u8,
+        #[serde(serialize_with = "serialize_with_format")]
+        hash_root: AnyHash,
+        #[serde(serialize_with = "serialize_with_format")]
+        pre_image: AnyHash,
+        signature_proof: SignatureProofInfo,
+    },
+
+    /// HTLC resolved before the timeout, signed by both parties
+    HtlcEarlyResolve {
+        htlc_sender_signature_proof: SignatureProofInfo,
+        htlc_recipient_signature_proof: SignatureProofInfo,
+    },
+
+    /// HTLC refunded to the sender after the timeout
+    HtlcTimeoutResolve {
+        signature_proof: SignatureProofInfo,
+    },
+}
+
+impl TransactionProof {
+    /// Decodes the proof of a transaction.
+    pub fn decode(transaction: &Transaction) -> Result<Self, SerializingError> {
+        let message = transaction.serialize_content();
+
+        if transaction.sender_type == AccountType::HTLC {
+            return Self::decode_htlc(&transaction.proof, &message);
+        }
+
+        let proof: SignatureProof = Deserialize::deserialize_from_vec(&transaction.proof)?;
+        Ok(TransactionProof::Signature(SignatureProofInfo::new(proof, &message)))
+    }
+
+    /// HTLC proofs start with the proof type, followed by the fields for that type.
+    fn decode_htlc(proof: &[u8], message: &[u8]) -> Result<Self, SerializingError> {
+        let mut reader = proof;
+        let proof_type: ProofType = Deserialize::deserialize(&mut reader)?;
+
+        Ok(match proof_type {
+            ProofType::RegularTransfer => {
+                let hash_algorithm: HashAlgorithm = Deserialize::deserialize(&mut reader)?;
+                let hash_depth: u8 = Deserialize::deserialize(&mut reader)?;
+                let hash_root: AnyHash = Deserialize::deserialize(&mut reader)?;
+                let pre_image: AnyHash = Deserialize::deserialize(&mut reader)?;
+                let signature_proof: SignatureProof = Deserialize::deserialize(&mut reader)?;
+                TransactionProof::HtlcRegularTransfer {
+                    hash_algorithm,
+                    hash_depth,
+                    hash_root,
+                    pre_image,
+                    signature_proof: SignatureProofInfo::new(signature_proof, message),
+                }
+            },
+            ProofType::EarlyResolve => {
+                let htlc_sender_signature_proof: SignatureProof = Deserialize::deserialize(&mut reader)?;
+                let htlc_recipient_signature_proof: SignatureProof = Deserialize::deserialize(&mut reader)?;
+                TransactionProof::HtlcEarlyResolve {
+                    htlc_sender_signature_proof: SignatureProofInfo::new(htlc_sender_signature_proof, message),
+                    htlc_recipient_signature_proof: SignatureProofInfo::new(htlc_recipient_signature_proof, message),
+                }
+            },
+            ProofType::TimeoutResolve => {
+                let signature_proof: SignatureProof = Deserialize::deserialize(&mut reader)?;
+                TransactionProof::HtlcTimeoutResolve {
+                    signature_proof: SignatureProofInfo::new(signature_proof, message),
+                }
+            },
+        })
+    }
+
+    fn signature_proofs(&self) -> Vec<&SignatureProofInfo> {
+        match self {
+            TransactionProof::Signature(proof) => vec![proof],
+            TransactionProof::HtlcRegularTransfer { signature_proof, .. } => vec![signature_proof],
+            TransactionProof::HtlcEarlyResolve { htlc_sender_signature_proof, htlc_recipient_signature_proof } => {
+                vec![htlc_sender_signature_proof, htlc_recipient_signature_proof]
+            },
+            TransactionProof::HtlcTimeoutResolve { signature_proof } => vec![signature_proof],
+        }
+    }
+
+    /// Checks that all signatures are valid and, for basic senders, that the signing key derives
+    /// the sender address. Contracts are signed by their owners, which we can't check without
+    /// the contract's state.
+    pub fn verify(&self, transaction: &Transaction) -> VerificationResult {
+        if let Some(proof) = self.signature_proofs().iter().find(|proof| !proof.is_valid) {
+            return VerificationResult::failed(format!("Invalid signature by {}", proof.signer_address.to_user_friendly_address()));
+        }
+
+        match self {
+            TransactionProof::Signature(proof) if transaction.sender_type == AccountType::Basic => {
+                VerificationResult::check(proof.signer_address == transaction.sender,
+                                          format!("Signed by {}, which is not the sender", proof.signer_address.to_user_friendly_address()))
+            },
+            _ => VerificationResult::verified(),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use nimiq_hash::Hash;
+use nimiq_block_albatross::{MacroBlock, MicroBlock, ViewChange};
+use nimiq_bls::bls12_381::{CompressedPublicKey, CompressedSignature};
+use nimiq_bls::lazy::LazyPublicKey;
+use nimiq_collections::grouped_list::GroupedList;
+use nimiq_primitives::policy::TWO_THIRD_SLOTS;
+
+
+pub type Validators = GroupedList<LazyPublicKey>;
+
+
+/// Outcome of a single signature check, shown as a badge on the block page.
+#[derive(Clone, Debug, Serialize)]
+pub struct VerificationResult {
+    verified: bool,
+    reason: Option<String>,
+}
+
+impl VerificationResult {
+    pub fn verified() -> Self {
+        Self {
+            verified: true,
+            reason: None,
+        }
+    }
+
+    pub fn failed<S: ToString>(reason: S) -> Self {
+        Self {
+            verified: false,
+            reason: Some(reason.to_string()),
+        }
+    }
+
+    pub fn check<S: ToString>(valid: bool, reason: S) -> Self {
+        if valid { Self::verified() } else { Self::failed(reason) }
+    }
+
+    pub fn from_result<E: Display>(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self::verified(),
+            Err(e) => Self::failed(e),
+        }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.verified
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BlockVerificationInfo {
+    /// `true` if all checks that apply to this block passed
+    verified: bool,
+
+    /// Micro block signature by the block producer
+    signature: Option<VerificationResult>,
+
+    /// Seed, which is the block producer's signature of the previous seed
+    seed: Option<VerificationResult>,
+
+    /// Aggregate signature of the view change proof, if the block has one
+    view_change: Option<VerificationResult>,
+
+    /// PBFT justification of a macro block
+    justification: Option<VerificationResult>,
+}
+
+impl BlockVerificationInfo {
+    fn new(signature: Option<VerificationResult>, seed: Option<VerificationResult>,
+           view_change: Option<VerificationResult>, justification: Option<VerificationResult>) -> Self {
+        let verified = [&signature, &seed, &view_change, &justification].iter()
+            .all(|check| check.as_ref().map(|c| c.is_verified()).unwrap_or(true));
+        Self {
+            verified,
+            signature,
+            seed,
+            view_change,
+            justification,
+        }
+    }
+
+    /// Verify a micro block.
+    ///
+    /// `producer_key` is the BLS key of the slot owner that was supposed to produce this block,
+    /// `prev_seed` the seed of the parent block and `validators` the validator set of the block's
+    /// epoch.
+    pub fn verify_micro_block(block: &MicroBlock, producer_key: Option<&CompressedPublicKey>, prev_seed: Option<&CompressedSignature>, validators: Option<&Validators>) -> Self {
+        let signature = Some(match producer_key {
+            Some(key) => verify_signature(key, &block.header, &block.justification.signature),
+            None => VerificationResult::failed("Unknown block producer"),
+        });
+
+        let seed = Some(verify_seed(producer_key, prev_seed, &block.header.seed));
+
+        let view_change = block.justification.view_change_proof.as_ref().map(|proof| {
+            match (validators, prev_seed) {
+                (Some(validators), Some(prev_seed)) => {
+                    let view_change = ViewChange {
+                        block_number: block.header.block_number,
+                        new_view_number: block.header.view_number,
+                        prev_seed: prev_seed.clone(),
+                    };
+                    VerificationResult::from_result(proof.verify(&view_change, validators, TWO_THIRD_SLOTS))
+                },
+                (None, _) => VerificationResult::failed("Validators of this epoch are unknown"),
+                (_, None) => VerificationResult::failed("Previous block is unknown"),
+            }
+        });
+
+        Self::new(signature, seed, view_change, None)
+    }
+
+    /// Verify a macro block.
+    ///
+    /// The justification is checked against `validators`, which must be the validators of the
+    /// previous epoch, i.e. the ones that were elected by the previous macro block.
+    pub fn verify_macro_block(block: &MacroBlock, producer_key: Option<&CompressedPublicKey>, prev_seed: Option<&CompressedSignature>, validators: Option<&Validators>) -> Self {
+        // The genesis block has no parent and nobody to sign it.
+        if block.header.block_number == 0 {
+            return Self::new(None, None, None, None);
+        }
+
+        let seed = Some(verify_seed(producer_key, prev_seed, &block.header.seed));
+
+        let justification = Some(match (&block.justification, validators) {
+            (Some(justification), Some(validators)) => {
+                let result = justification.verify(block.header.hash(), validators, TWO_THIRD_SLOTS);
+                VerificationResult::from_result(result)
+            },
+            (None, _) => VerificationResult::failed("Macro block has no justification"),
+            (_, None) => VerificationResult::failed("Validators of the previous epoch are unknown"),
+        });
+
+        Self::new(None, seed, None, justification)
+    }
+}
+
+fn verify_signature<M: Hash>(key: &CompressedPublicKey, message: &M, signature: &CompressedSignature) -> VerificationResult {
+    let public_key = match key.uncompress() {
+        Ok(public_key) => public_key,
+        Err(e) => return VerificationResult::failed(format!("Invalid public key: {}", e)),
+    };
+    let signature = match signature.uncompress() {
+        Ok(signature) => signature,
+        Err(e) => return VerificationResult::failed(format!("Invalid signature: {}", e)),
+    };
+    VerificationResult::check(public_key.verify(message, &signature), "Signature doesn't match the block producer's key")
+}
+
+fn verify_seed(producer_key: Option<&CompressedPublicKey>, prev_seed: Option<&CompressedSignature>, seed: &CompressedSignature) -> VerificationResult {
+    match (producer_key, prev_seed) {
+        (Some(key), Some(prev_seed)) => verify_signature(key, prev_seed, seed),
+        (None, _) => VerificationResult::failed("Unknown block producer"),
+        (_, None) => VerificationResult::failed("Previous block is unknown"),
+    }
+}
@@ -6,6 +6,97 @@ table! {
     }
 }
 
+table! {
+    blocks (id) {
+        id -> Int4,
+        block_hash -> Bpchar,
+        block_number -> Int4,
+        is_macro -> Bool,
+        num_transactions -> Int4,
+    }
+}
+
+table! {
+    contract_parties (id) {
+        id -> Int4,
+        contract_address -> Bpchar,
+        party_address -> Bpchar,
+        role -> Text,
+    }
+}
+
+table! {
+    contracts (id) {
+        id -> Int4,
+        address -> Bpchar,
+        contract_type -> Text,
+        creation_txid -> Bpchar,
+        creation_block_number -> Int4,
+    }
+}
+
+table! {
+    epoch_rewards (id) {
+        id -> Int4,
+        epoch -> Int4,
+        block_hash -> Bpchar,
+        staker_address -> Bpchar,
+        reward_address -> Bpchar,
+        num_slots -> Int4,
+        num_slashed -> Int4,
+        reward -> Int8,
+        slash_deduction -> Int8,
+        payout -> Int8,
+    }
+}
+
+table! {
+    htlc_events (id) {
+        id -> Int4,
+        address -> Bpchar,
+        txid -> Bpchar,
+        block_number -> Int4,
+        kind -> Text,
+        amount -> Int8,
+        pre_image -> Nullable<Text>,
+    }
+}
+
+table! {
+    htlcs (id) {
+        id -> Int4,
+        address -> Bpchar,
+        creation_txid -> Bpchar,
+        creation_block_number -> Int4,
+        sender -> Bpchar,
+        recipient -> Bpchar,
+        hash_algorithm -> Text,
+        hash_root -> Text,
+        hash_count -> Int4,
+        timeout -> Int4,
+        total_amount -> Int8,
+    }
+}
+
+table! {
+    stakes (id) {
+        id -> Int4,
+        staker_address -> Bpchar,
+        reward_address -> Bpchar,
+        validator_key -> Bpchar,
+        block_number -> Int4,
+    }
+}
+
+table! {
+    transaction_messages (id) {
+        id -> Int4,
+        txid -> Bpchar,
+        block_number -> Int4,
+        message -> Text,
+    }
+}
+
 table! {
     transactions (id) {
         id -> Int4,
@@ -20,5 +111,13 @@ table! {
 
 allow_tables_to_appear_in_same_query!(
     account_aliases,
+    blocks,
+    contract_parties,
+    contracts,
+    epoch_rewards,
+    htlc_events,
+    htlcs,
+    stakes,
+    transaction_messages,
     transactions,
 );
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use serde::Serialize;
 use serde::ser::{Serializer, SerializeSeq};
 use chrono::{DateTime, Utc};
 use chrono::offset::TimeZone;
@@ -127,6 +128,15 @@ pub(crate) fn serialize_datetime<S>(dt: &DateTime<Utc>, serializer: S) -> Result
     serializer.serialize_str(&s)
 }
 
+pub(crate) fn serialize_datetime_opt<S>(dt_opt: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer,
+{
+    match dt_opt {
+        Some(dt) => serialize_datetime(dt, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 pub(crate) fn serialize_bitset<S>(bitset: &BitSet, serializer: S) -> Result<S::Ok, S::Error>
     where S: Serializer,
 {
@@ -136,3 +146,47 @@ pub(crate) fn serialize_bitset<S>(bitset: &BitSet, serializer: S) -> Result<S::O
     }
     seq.end()
 }
+
+
+/// Returns the data as string, if it's valid UTF-8 and only contains printable characters
+pub fn printable_utf8(data: &[u8]) -> Option<String> {
+    if data.is_empty() {
+        return None;
+    }
+    let s = std::str::from_utf8(data).ok()?;
+    if s.chars().all(|c| !c.is_control() || c == '\n' || c == '\r' || c == '\t') {
+        Some(s.to_string())
+    }
+    else {
+        None
+    }
+}
+
+const HEX_DUMP_WIDTH: usize = 16;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HexDumpLine {
+    offset: String,
+    hex: String,
+    ascii: String,
+}
+
+/// Formats data like `hexdump -C`, with 16 bytes per line
+pub fn hex_dump(data: &[u8]) -> Vec<HexDumpLine> {
+    data.chunks(HEX_DUMP_WIDTH).enumerate()
+        .map(|(i, chunk)| {
+            let hex = chunk.iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<String>>()
+                .join(" ");
+            let ascii = chunk.iter()
+                .map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' })
+                .collect();
+            HexDumpLine {
+                offset: format!("{:08x}", i * HEX_DUMP_WIDTH),
+                hex,
+                ascii,
+            }
+        })
+        .collect()
+}